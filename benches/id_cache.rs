@@ -0,0 +1,80 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Compares semantic id computation with and without [`IdCache`] on a deeply
+//! shared DAG of 5k types. Run with `cargo bench --bench id_cache`.
+
+#[macro_use]
+extern crate strict_types;
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use strict_encoding::Sizing;
+use strict_types::ast::IdCache;
+use strict_types::{SemId, Ty};
+
+const DEPTH: u16 = 2500;
+const ROUNDS: u32 = 20;
+
+fn dag() -> Vec<Ty<SemId>> {
+    let mut types = vec![Ty::<SemId>::U8];
+    let mut prev = Ty::<SemId>::U8.sem_id_unnamed();
+    for no in 0..DEPTH {
+        let list = Ty::<SemId>::List(prev, Sizing::new(0, no as u64 + 1));
+        let id = list.sem_id_unnamed();
+        let strct = Ty::<SemId>::Struct(fields!("a" => prev, "b" => id));
+        prev = strct.sem_id_unnamed();
+        types.push(list);
+        types.push(strct);
+    }
+    types
+}
+
+fn measure(name: &str, mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..ROUNDS {
+        f();
+    }
+    let elapsed = start.elapsed() / ROUNDS;
+    println!("{name:>10}: {elapsed:?} per round");
+    elapsed
+}
+
+fn main() {
+    let types = dag();
+    println!("{} types, {ROUNDS} rounds", types.len());
+
+    measure("uncached", || {
+        for ty in &types {
+            black_box(ty.sem_id_unnamed());
+        }
+    });
+
+    let mut cache = IdCache::new();
+    measure("cached", || {
+        for ty in &types {
+            black_box(ty.id_cached(&mut cache));
+        }
+    });
+    assert_eq!(cache.len(), types.len());
+}
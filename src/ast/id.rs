@@ -20,6 +20,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::HashMap;
 use std::fmt::{self, Display, Formatter};
 use std::hash::Hash;
 use std::str::FromStr;
@@ -37,7 +38,7 @@ use crate::{Cls, CommitConsume, TranspileRef, Ty, TypeRef};
 
 /// Semantic type id, which commits to the type memory layout, name and field/variant names.
 #[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From)]
-#[wrapper(Deref, BorrowSlice, Hex, Index, RangeOps)]
+#[wrapper(Deref, AsSlice, BorrowSlice, Hex, Index, RangeOps)]
 #[derive(StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB)]
 #[cfg_attr(
@@ -75,6 +76,23 @@ impl Display for SemId {
 
 pub const SEM_ID_TAG: [u8; 32] = *b"urn:ubideco:strict-types:typ:v01";
 
+/// Structural type id, which commits only to the type memory layout, ignoring type name and
+/// field/variant names.
+///
+/// Unlike [`SemId`], structural id doesn't change when a field or a variant gets renamed, which
+/// makes it suitable for caching layout-dependent data. Nested types are committed to by the ids
+/// used as references, i.e. for `Ty<SemId>` renames inside nested types still affect the value.
+#[derive(Wrapper, Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, From, Display)]
+#[wrapper(Deref, BorrowSlice, Hex, Index, RangeOps)]
+#[display(LowerHex)]
+pub struct StructuralId(
+    #[from]
+    #[from([u8; 32])]
+    Bytes32,
+);
+
+pub const STRUCTURAL_ID_TAG: [u8; 32] = *b"urn:ubideco:strict-types:lay:v01";
+
 impl SemId {
     pub fn unit() -> Self { SemId::default() }
 }
@@ -103,7 +121,35 @@ impl<Ref: TypeRef> Ty<Ref> {
     }
 }
 
+/// Cache memoizing semantic ids of unnamed types, used to avoid repeated hashing of the same
+/// types when assembling large type systems.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct IdCache(HashMap<Ty<SemId>, SemId>);
+
+impl IdCache {
+    pub fn new() -> Self { Self::default() }
+
+    pub fn len(&self) -> usize { self.0.len() }
+
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+}
+
 impl Ty<SemId> {
+    /// Constructs option type, which is a union of `none` unit variant with tag 0 and `some`
+    /// variant with tag 1 containing the `inner` type.
+    pub fn option(inner: SemId) -> Self { Self::option_with(SemId::unit(), inner) }
+
+    /// Computes the same id as [`Ty::sem_id_unnamed`], re-using ids already present in the
+    /// `cache`.
+    pub fn id_cached(&self, cache: &mut IdCache) -> SemId {
+        if let Some(id) = cache.0.get(self) {
+            return *id;
+        }
+        let id = self.sem_id_unnamed();
+        cache.0.insert(self.clone(), id);
+        id
+    }
+
     pub fn sem_id_unnamed(&self) -> SemId {
         // For unnamed 1-tuples we must not produce a new sem id
         if let Some(inner) = self.as_wrapped_ty() {
@@ -112,6 +158,93 @@ impl Ty<SemId> {
 
         self.sem_id_inner(None)
     }
+
+    /// Computes [`StructuralId`] of the type, which commits to the type layout but not to the
+    /// names of its fields and variants.
+    pub fn structural_id(&self) -> StructuralId {
+        let tag = sha2::Sha256::new_with_prefix(STRUCTURAL_ID_TAG).finalize();
+        let mut hasher = sha2::Sha256::new();
+        hasher.commit_consume(tag);
+        hasher.commit_consume(tag);
+        self.cls().sem_commit(&mut hasher);
+        match self {
+            Ty::Primitive(prim) => hasher.commit_consume([prim.into_code()]),
+            Ty::UnicodeChar => {}
+            Ty::Enum(variants) => {
+                for variant in variants {
+                    hasher.commit_consume([variant.tag]);
+                }
+            }
+            Ty::Union(variants) => {
+                for (variant, ty) in variants {
+                    hasher.commit_consume([variant.tag]);
+                    ty.sem_commit(&mut hasher);
+                }
+            }
+            Ty::Tuple(fields) => fields.sem_commit(&mut hasher),
+            Ty::Struct(fields) => {
+                for field in fields {
+                    field.ty.sem_commit(&mut hasher);
+                }
+            }
+            Ty::Array(ty, len) => {
+                ty.sem_commit(&mut hasher);
+                hasher.commit_consume(len.to_le_bytes());
+            }
+            Ty::List(ty, sizing) | Ty::Set(ty, sizing) => {
+                ty.sem_commit(&mut hasher);
+                sizing.sem_commit(&mut hasher);
+            }
+            Ty::Map(key, ty, sizing) => {
+                key.sem_commit(&mut hasher);
+                ty.sem_commit(&mut hasher);
+                sizing.sem_commit(&mut hasher);
+            }
+        }
+        StructuralId::from_byte_array(hasher.finalize())
+    }
+
+    /// Produces machine-oriented canonical textual representation of the type, which is not
+    /// affected by changes in the human-readable [`Display`] format.
+    ///
+    /// The representation is an S-expression, where type references are given as hex-encoded
+    /// semantic ids, and names are quoted.
+    pub fn to_canonical_string(&self) -> String {
+        let sizing = |sizing: &Sizing| format!("{} {}", sizing.min, sizing.max);
+        match self {
+            Ty::Primitive(prim) => format!("(primitive {})", prim.into_code()),
+            Ty::UnicodeChar => s!("(unicode)"),
+            Ty::Enum(variants) => {
+                let variants = variants
+                    .iter()
+                    .map(|v| format!(" (variant \"{}\" {})", v.name, v.tag))
+                    .collect::<String>();
+                format!("(enum{variants})")
+            }
+            Ty::Union(variants) => {
+                let variants = variants
+                    .iter()
+                    .map(|(v, id)| format!(" (variant \"{}\" {} {id:x})", v.name, v.tag))
+                    .collect::<String>();
+                format!("(union{variants})")
+            }
+            Ty::Tuple(fields) => {
+                let fields = fields.iter().map(|id| format!(" {id:x}")).collect::<String>();
+                format!("(tuple{fields})")
+            }
+            Ty::Struct(fields) => {
+                let fields = fields
+                    .iter()
+                    .map(|f| format!(" (field \"{}\" {:x})", f.name, f.ty))
+                    .collect::<String>();
+                format!("(struct{fields})")
+            }
+            Ty::Array(id, len) => format!("(array {id:x} {len})"),
+            Ty::List(id, s) => format!("(list {id:x} {})", sizing(s)),
+            Ty::Set(id, s) => format!("(set {id:x} {})", sizing(s)),
+            Ty::Map(key, id, s) => format!("(map {key:x} {id:x} {})", sizing(s)),
+        }
+    }
 }
 
 impl<Ref: LibSubref> Ty<Ref> {
@@ -127,6 +260,10 @@ impl<Ref: LibSubref> Ty<Ref> {
 
 // TODO: Make sure we do a right thing here - a valid sem id can be produced from the TranspileRef
 impl Ty<TranspileRef> {
+    /// Constructs option type, which is a union of `none` unit variant with tag 0 and `some`
+    /// variant with tag 1 containing the `inner` type.
+    pub fn option(inner: TranspileRef) -> Self { Self::option_with(TranspileRef::unit(), inner) }
+
     pub fn sem_id_named(&self, name: &TypeName) -> SemId { self.sem_id_inner(Some(name)) }
 }
 
@@ -270,4 +407,76 @@ mod test {
     fn byte() {
         assert!(Ty::<SemId>::Primitive(Primitive::BYTE).is_byte());
     }
+
+    #[test]
+    fn id_cached() {
+        let mut cache = IdCache::new();
+        let mut types = vec![Ty::<SemId>::U8];
+        let mut prev = Ty::<SemId>::U8.id_cached(&mut cache);
+        for no in 0..2500u16 {
+            let list = Ty::<SemId>::List(prev, Sizing::new(0, no as u64 + 1));
+            let id = list.id_cached(&mut cache);
+            let strct = Ty::<SemId>::Struct(fields!("a" => prev, "b" => id));
+            prev = strct.id_cached(&mut cache);
+            types.push(list);
+            types.push(strct);
+        }
+        assert_eq!(cache.len(), types.len());
+        for ty in &types {
+            assert_eq!(ty.id_cached(&mut cache), ty.sem_id_unnamed());
+        }
+        assert_eq!(cache.len(), types.len());
+    }
+
+    #[test]
+    fn raw_bytes() {
+        let id = Ty::<SemId>::U8.sem_id_unnamed();
+        assert_eq!(id.as_slice().len(), 32);
+        assert_eq!(AsRef::<[u8]>::as_ref(&id), id.as_slice());
+        assert_eq!(SemId::from_byte_array(id.to_byte_array()), id);
+    }
+
+    #[test]
+    fn structural_id() {
+        let byte = SemId::byte();
+        let a = Ty::<SemId>::Struct(fields!("a" => byte, "b" => byte));
+        let b = Ty::<SemId>::Struct(fields!("a" => byte, "c" => byte));
+        assert_eq!(a.structural_id(), b.structural_id());
+        assert_ne!(a.sem_id_unnamed(), b.sem_id_unnamed());
+
+        let c = Ty::<SemId>::Struct(fields!("a" => byte, "b" => SemId::unicode_char()));
+        assert_ne!(a.structural_id(), c.structural_id());
+        let d = Ty::<SemId>::Tuple(vec![byte, byte].try_into().unwrap());
+        assert_ne!(a.structural_id(), d.structural_id());
+
+        let e = Ty::<SemId>::Enum(variants!("x", "y"));
+        let f = Ty::<SemId>::Enum(variants!("z", "w"));
+        assert_eq!(e.structural_id(), f.structural_id());
+        assert_ne!(e.sem_id_unnamed(), f.sem_id_unnamed());
+    }
+
+    #[test]
+    fn canonical_string() {
+        let byte = SemId::byte();
+        let a = Ty::<SemId>::Struct(fields!("a" => byte, "b" => byte));
+        let b = Ty::<SemId>::Struct(fields!("a" => byte, "b" => byte));
+        assert_eq!(a.to_canonical_string(), b.to_canonical_string());
+        assert_eq!(
+            a.to_canonical_string(),
+            format!("(struct (field \"a\" {byte:x}) (field \"b\" {byte:x}))")
+        );
+        assert_eq!(
+            Ty::<SemId>::List(byte, Sizing::new(1, 10)).to_canonical_string(),
+            format!("(list {byte:x} 1 10)")
+        );
+        assert_ne!(
+            Ty::<SemId>::List(byte, Sizing::new(1, 10)).to_canonical_string(),
+            Ty::<SemId>::List(byte, Sizing::new(1, 11)).to_canonical_string()
+        );
+        assert_eq!(
+            Ty::<SemId>::Enum(variants!("x", "y")).to_canonical_string(),
+            r#"(enum (variant "x" 0) (variant "y" 1))"#
+        );
+        assert_eq!(Ty::<SemId>::U16.to_canonical_string(), "(primitive 2)");
+    }
 }
@@ -27,11 +27,12 @@ mod iter;
 mod encoding;
 mod translate;
 
-pub use id::{SemCommit, SemId, SEM_ID_TAG};
+pub use id::{IdCache, SemCommit, SemId, StructuralId, SEM_ID_TAG, STRUCTURAL_ID_TAG};
 pub use iter::{CheckError, IntoIter, Iter};
 pub use path::{Path, PathError, Step};
 pub use translate::Translate;
+pub(crate) use ty::SizingExt;
 pub use ty::{
-    Cls, EnumVariants, Field, ItemCase, NamedFields, PrimitiveRef, Ty, TypeRef, UnionVariants,
-    UnnamedFields,
+    Cls, ClsError, EnumVariants, Field, ItemCase, NamedFields, OptionNorm, PrimitiveRef,
+    SizingError, TagError, Ty, TypeRef, UnionVariants, UnnamedFields,
 };
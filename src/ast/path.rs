@@ -128,6 +128,69 @@ impl<Ref: TypeRef> Ty<Ref> {
         Ok(ty)
     }
 
+    /// Recursively walks the type and all of its nested types, calling `f` with each of them
+    /// and a path to it in `/field/0/[]` form. The root type is reported with an empty path.
+    ///
+    /// References which are not inline types are resolved with the `resolver`; unresolved and
+    /// recursive references are not walked into.
+    pub fn walk_with_path<'r>(
+        &self,
+        resolver: &impl Fn(&Ref) -> Option<&'r Ty<Ref>>,
+        mut f: impl FnMut(&str, &Ty<Ref>),
+    ) where
+        Ref: 'r,
+    {
+        self.walk_inner(&mut String::new(), resolver, &mut f, &mut vec![])
+    }
+
+    fn walk_inner<'r>(
+        &self,
+        path: &mut String,
+        resolver: &impl Fn(&Ref) -> Option<&'r Ty<Ref>>,
+        f: &mut impl FnMut(&str, &Ty<Ref>),
+        stack: &mut Vec<Ref>,
+    ) where
+        Ref: 'r,
+    {
+        f(path, self);
+        let children: Vec<(Step, &Ref)> = match self {
+            Ty::Primitive(_) | Ty::UnicodeChar | Ty::Enum(_) => vec![],
+            Ty::Union(variants) => variants
+                .iter()
+                .map(|(variant, ty)| (Step::Variant(variant.name.clone()), ty))
+                .collect(),
+            Ty::Struct(fields) => fields
+                .iter()
+                .map(|field| (Step::NamedField(field.name.clone()), &field.ty))
+                .collect(),
+            Ty::Tuple(fields) => fields
+                .iter()
+                .enumerate()
+                .map(|(pos, ty)| (Step::UnnamedField(pos as u8), ty))
+                .collect(),
+            Ty::Array(ty, _) => vec![(Step::Index, ty)],
+            Ty::List(ty, _) => vec![(Step::List, ty)],
+            Ty::Set(ty, _) => vec![(Step::Set, ty)],
+            Ty::Map(key, ty, _) => vec![(Step::MapKey, key), (Step::MapValue, ty)],
+        };
+        for (step, r) in children {
+            if stack.contains(r) {
+                continue;
+            }
+            let len = path.len();
+            path.push('/');
+            path.push_str(step.to_string().trim_start_matches('.'));
+            if let Some(ty) = r.as_ty() {
+                ty.walk_inner(path, resolver, f, stack);
+            } else if let Some(ty) = resolver(r) {
+                stack.push(r.clone());
+                ty.walk_inner(path, resolver, f, stack);
+                stack.pop();
+            }
+            path.truncate(len);
+        }
+    }
+
     pub fn count_type_refs(&self) -> u8 {
         match self {
             Ty::Primitive(_) => 0,
@@ -142,3 +205,43 @@ impl<Ref: TypeRef> Ty<Ref> {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use encoding::Sizing;
+
+    use super::*;
+    use crate::{Cls, SemId};
+
+    #[test]
+    fn walk_with_path() {
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let items = Ty::<SemId>::List(u8, Sizing::U8);
+        let pair = Ty::<SemId>::Tuple(vec![u8, items.sem_id_unnamed()].try_into().unwrap());
+        let rec = Ty::<SemId>::Struct(fields!(
+            "items" => items.sem_id_unnamed(),
+            "pair" => pair.sem_id_unnamed(),
+        ));
+        let types = bmap! {
+            u8 => Ty::U8,
+            items.sem_id_unnamed() => items.clone(),
+            pair.sem_id_unnamed() => pair,
+        };
+
+        let mut paths = BTreeMap::new();
+        rec.walk_with_path(&|id| types.get(id), |path, ty| {
+            paths.insert(path.to_owned(), ty.cls());
+        });
+        assert_eq!(paths, bmap! {
+            s!("") => Cls::Struct,
+            s!("/items") => Cls::List,
+            s!("/items/[]") => Cls::Primitive,
+            s!("/pair") => Cls::Tuple,
+            s!("/pair/0") => Cls::Primitive,
+            s!("/pair/1") => Cls::List,
+            s!("/pair/1/[]") => Cls::Primitive
+        });
+    }
+}
@@ -20,15 +20,19 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::cmp::Ordering;
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
 use std::ops::Deref;
 
 use amplify::confinement::{Confined, NonEmptyOrdMap, NonEmptyOrdSet, NonEmptyVec};
+use amplify::num::u24;
 use amplify::{confinement, Wrapper};
 use encoding::VariantName;
 use strict_encoding::{
-    FieldName, Primitive, Sizing, StrictDecode, StrictDumb, StrictEncode, Variant, STRICT_TYPES_LIB,
+    FieldName, NumCls, Primitive, Sizing, StrictDecode, StrictDumb, StrictEncode, Variant,
+    STRICT_TYPES_LIB,
 };
 
 use super::id::SemCommit;
@@ -46,12 +50,29 @@ pub trait TypeRef:
     fn is_unicode_char(&self) -> bool { false }
 }
 
+/// Width of the length prefix used by strict encoding for collections with the given sizing.
+pub(crate) trait SizingExt {
+    fn byte_size(&self) -> usize;
+}
+
+impl SizingExt for Sizing {
+    fn byte_size(&self) -> usize {
+        match self.max {
+            one if one <= u8::MAX as u64 => 1,
+            two if two <= u16::MAX as u64 => 2,
+            three if three <= u24::MAX.into_u64() => 3,
+            four if four <= u32::MAX as u64 => 4,
+            _ => 8,
+        }
+    }
+}
+
 pub trait PrimitiveRef: TypeRef {
     fn byte() -> Self;
     fn unicode_char() -> Self;
 }
 
-#[derive(Copy, Clone, Eq, PartialEq, Debug, Display)]
+#[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB, tags = repr, into_u8, try_from_u8)]
 #[display(lowercase)]
@@ -85,8 +106,36 @@ impl Cls {
         Cls::Set,
         Cls::Map,
     ];
+
+    /// Parses type class from its byte representation, reporting the accepted range of values
+    /// on failure.
+    pub fn from_byte(value: u8) -> Result<Cls, ClsError> {
+        Cls::try_from(value).map_err(|err| ClsError(err.1))
+    }
+
+    /// Detects classes of collection types: arrays, lists, sets and maps.
+    pub const fn is_collection(self) -> bool {
+        matches!(self, Cls::Array | Cls::List | Cls::Set | Cls::Map)
+    }
+
+    /// Detects classes of types composed of named or positional items: enums, unions,
+    /// structures and tuples.
+    pub const fn is_composite(self) -> bool {
+        matches!(self, Cls::Enum | Cls::Union | Cls::Struct | Cls::Tuple)
+    }
+
+    /// Detects classes of types represented by a single value: primitives, unicode characters
+    /// and ASCII strings.
+    pub const fn is_scalar(self) -> bool {
+        matches!(self, Cls::Primitive | Cls::Unicode | Cls::AsciiStr)
+    }
 }
 
+/// Error parsing type class from a byte.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display("invalid Cls byte {0}, expected 0..=10")]
+pub struct ClsError(pub u8);
+
 impl<Ref: TypeRef> Ty<Ref> {
     pub const fn cls(&self) -> Cls {
         match self {
@@ -102,9 +151,75 @@ impl<Ref: TypeRef> Ty<Ref> {
             Ty::Map(_, _, _) => Cls::Map,
         }
     }
+
+    /// Returns canonical human-readable name of primitive types (like `U32` or `Unit`) and of
+    /// the unicode character type (`Unicode`), or `None` for all other types.
+    pub fn primitive_name(&self) -> Option<String> {
+        match self {
+            Ty::Primitive(prim) if *prim == Primitive::UNIT => Some(s!("Unit")),
+            Ty::Primitive(prim) => Some(prim.to_string()),
+            Ty::UnicodeChar => Some(s!("Unicode")),
+            _ => None,
+        }
+    }
+
+    /// Returns a compact one-token description of the type kind for use in diagnostics.
+    ///
+    /// Unlike [`Cls`], distinguishes optional unions (`"option"`) from the other unions and
+    /// reports the concrete primitive (like `"u32"`) instead of a generic `"primitive"`.
+    pub fn kind_str(&self) -> &'static str {
+        match self {
+            Ty::Primitive(prim) => match *prim {
+                Primitive::UNIT => "unit",
+                Primitive::BYTE => "byte",
+                Primitive::U8 => "u8",
+                Primitive::U16 => "u16",
+                Primitive::U24 => "u24",
+                Primitive::U32 => "u32",
+                Primitive::U40 => "u40",
+                Primitive::U48 => "u48",
+                Primitive::U56 => "u56",
+                Primitive::U64 => "u64",
+                Primitive::U128 => "u128",
+                Primitive::U256 => "u256",
+                Primitive::U512 => "u512",
+                Primitive::U1024 => "u1024",
+                Primitive::I8 => "i8",
+                Primitive::I16 => "i16",
+                Primitive::I24 => "i24",
+                Primitive::I32 => "i32",
+                Primitive::I40 => "i40",
+                Primitive::I48 => "i48",
+                Primitive::I56 => "i56",
+                Primitive::I64 => "i64",
+                Primitive::I128 => "i128",
+                Primitive::I256 => "i256",
+                Primitive::I512 => "i512",
+                Primitive::I1024 => "i1024",
+                Primitive::F16B => "f16b",
+                Primitive::F16 => "f16",
+                Primitive::F32 => "f32",
+                Primitive::F64 => "f64",
+                Primitive::F80 => "f80",
+                Primitive::F128 => "f128",
+                Primitive::F256 => "f256",
+                _ => "primitive",
+            },
+            Ty::UnicodeChar => "unicode",
+            Ty::Enum(_) => "enum",
+            Ty::Union(_) if self.is_option() => "option",
+            Ty::Union(_) => "union",
+            Ty::Tuple(_) => "tuple",
+            Ty::Struct(_) => "struct",
+            Ty::Array(_, _) => "array",
+            Ty::List(_, _) => "list",
+            Ty::Set(_, _) => "set",
+            Ty::Map(_, _, _) => "map",
+        }
+    }
 }
 
-#[derive(Clone, PartialEq, Eq, Debug, From)]
+#[derive(Clone, Eq, Debug, From)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
 #[strict_type(lib = STRICT_TYPES_LIB, tags = custom)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize), serde(crate = "serde_crate"))]
@@ -190,10 +305,47 @@ impl<Ref: TypeRef> Ty<Ref> {
     pub fn struc(fields: NamedFields<Ref>) -> Self { Ty::Struct(fields) }
     pub fn tuple(fields: UnnamedFields<Ref>) -> Self { Ty::Tuple(fields) }
 
+    /// Constructs fixed-size array, checking that its maximal serialized size doesn't exceed
+    /// strict encoding limit of 2^24 bytes. The maximal serialized size of the element type is
+    /// provided by the `elem_size` resolver.
+    pub fn array(
+        ty: Ref,
+        len: u16,
+        elem_size: impl FnOnce(&Ref) -> usize,
+    ) -> Result<Self, SizingError> {
+        let elem_size = elem_size(&ty);
+        match elem_size.checked_mul(len as usize) {
+            Some(size) if size <= u24::MAX.into_usize() => Ok(Ty::Array(ty, len)),
+            _ => Err(SizingError {
+                elem_size,
+                len: len as u64,
+            }),
+        }
+    }
     pub fn list(ty: Ref, sizing: Sizing) -> Self { Ty::List(ty, sizing) }
     pub fn set(ty: Ref, sizing: Sizing) -> Self { Ty::Set(ty, sizing) }
     pub fn map(key: Ref, val: Ref, sizing: Sizing) -> Self { Ty::Map(key, val, sizing) }
 
+    /// Constructs map, checking that the maximal serialized size of all its entries, given by
+    /// the maximal serialized sizes of the key and value types, doesn't exceed strict encoding
+    /// limit of 2^24 bytes.
+    pub fn map_checked(
+        key: Ref,
+        val: Ref,
+        sizing: Sizing,
+        key_size: usize,
+        val_size: usize,
+    ) -> Result<Self, SizingError> {
+        let elem_size = key_size.saturating_add(val_size);
+        match elem_size.checked_mul(sizing.max as usize) {
+            Some(size) if size <= u24::MAX.into_usize() => Ok(Ty::Map(key, val, sizing)),
+            _ => Err(SizingError {
+                elem_size,
+                len: sizing.max,
+            }),
+        }
+    }
+
     pub fn is_char_enum(&self) -> bool {
         if let Ty::Tuple(fields) = self {
             fields.first().and_then(Ref::as_ty).map(Self::is_char_enum).unwrap_or_default()
@@ -203,6 +355,26 @@ impl<Ref: TypeRef> Ty<Ref> {
             false
         }
     }
+    /// Detects enums used as bit-flag sets, where each variant tag is a distinct single bit.
+    pub fn is_bit_flags(&self) -> bool {
+        matches!(self, Ty::Enum(variants)
+            if variants.len() > 1 && variants.iter().all(|variant| variant.tag.is_power_of_two()))
+    }
+    /// Detects enums representing a domain of integer values, where each variant is named after
+    /// its tag (`_0`, `_1`, ...).
+    pub fn is_numeric_enum(&self) -> bool {
+        matches!(self, Ty::Enum(variants)
+            if variants.iter().all(|variant| variant.name.as_str() == format!("_{}", variant.tag)))
+    }
+    /// Returns the first and the last tag of an enum with no gaps between its variant tags.
+    pub fn contiguous_tags(&self) -> Option<(u8, u8)> {
+        let Ty::Enum(variants) = self else {
+            return None;
+        };
+        let min = variants.first()?.tag;
+        let max = variants.last()?.tag;
+        (variants.len() == (max - min) as usize + 1).then_some((min, max))
+    }
     pub fn is_compound(&self) -> bool {
         match self {
             Ty::Tuple(fields) if fields.len() > 1 => true,
@@ -219,6 +391,14 @@ impl<Ref: TypeRef> Ty<Ref> {
     pub fn is_newtype(&self) -> bool { matches!(self, Ty::Tuple(fields) if fields.len() == 1) }
     pub fn is_byte_array(&self) -> bool { matches!(self, Ty::Array(ty, _) if ty.is_byte()) }
     pub fn is_option(&self) -> bool { self.as_some().is_some() }
+    /// Distinguishes optional types from all other types, providing the inner type of the
+    /// option.
+    pub fn normalize_option(&self) -> OptionNorm<'_, Ref> {
+        match self.as_some() {
+            Some(inner) => OptionNorm::Optional(inner),
+            None => OptionNorm::Plain(self),
+        }
+    }
     pub fn as_some(&self) -> Option<&Ref> {
         match self {
             Ty::Union(variants)
@@ -233,6 +413,332 @@ impl<Ref: TypeRef> Ty<Ref> {
             _ => None,
         }
     }
+    /// Returns reference to the type wrapped into an option union, or `None` if the type is not
+    /// an option.
+    #[inline]
+    pub fn option_inner(&self) -> Option<&Ref> { self.as_some() }
+    /// Returns reference to the sole field type of a single-field tuple or structure, or `None`
+    /// for all other types.
+    pub fn newtype_inner(&self) -> Option<&Ref> {
+        match self {
+            Ty::Tuple(fields) if fields.len() == 1 => fields.first(),
+            Ty::Struct(fields) if fields.len() == 1 => fields.first().map(|f| &f.ty),
+            _ => None,
+        }
+    }
+
+    /// Returns primitive code if the type is a primitive. Unicode characters are not primitives
+    /// and return `None`.
+    pub fn as_primitive(&self) -> Option<Primitive> {
+        match self {
+            Ty::Primitive(prim) => Some(*prim),
+            _ => None,
+        }
+    }
+
+    /// Detects whether any of the types directly referenced from this type (not transitively)
+    /// is the provided reference.
+    pub fn contains_ref(&self, needle: &Ref) -> bool { self.type_refs().any(|(r, _)| r == needle) }
+
+    /// Collapses union having a single variant into its inner type. If the inner type is not
+    /// inline, the union is replaced with a single-field tuple wrapping it. All other types,
+    /// including options, are returned unchanged.
+    ///
+    /// Since this changes the semantic id of the type, it must be used only before the type
+    /// library or type system is finalized.
+    pub fn simplify(self) -> Ty<Ref> {
+        match self {
+            Ty::Union(variants) if variants.len() == 1 => {
+                let (_, inner) = variants.into_iter().next().expect("single variant");
+                match inner.as_ty() {
+                    Some(ty) => ty.clone(),
+                    None => Ty::Tuple(
+                        UnnamedFields::try_from(vec![inner]).expect("single field fits tuple"),
+                    ),
+                }
+            }
+            ty => ty,
+        }
+    }
+
+    /// Detects whether the type is serialized into zero bytes: it is a unit type, an array of
+    /// zero-sized elements (or of zero length), or a structure or tuple with all fields being
+    /// zero-sized.
+    ///
+    /// References which are not inline types are resolved with the `resolver`; unresolved and
+    /// recursive references are considered non-zero-sized.
+    pub fn is_zero_sized<'r>(&self, resolver: &impl Fn(&Ref) -> Option<&'r Ty<Ref>>) -> bool
+    where Ref: 'r {
+        self.is_zero_sized_inner(resolver, &mut vec![])
+    }
+
+    fn is_zero_sized_inner<'r>(
+        &self,
+        resolver: &impl Fn(&Ref) -> Option<&'r Ty<Ref>>,
+        stack: &mut Vec<Ref>,
+    ) -> bool
+    where
+        Ref: 'r,
+    {
+        let mut check = |r: &Ref| {
+            if stack.contains(r) {
+                return false;
+            }
+            if let Some(ty) = r.as_ty() {
+                return ty.is_zero_sized_inner(resolver, stack);
+            }
+            let Some(ty) = resolver(r) else {
+                return false;
+            };
+            stack.push(r.clone());
+            let zero_sized = ty.is_zero_sized_inner(resolver, stack);
+            stack.pop();
+            zero_sized
+        };
+        match self {
+            Ty::Primitive(prim) => *prim == Primitive::UNIT,
+            Ty::Array(_, 0) => true,
+            Ty::Array(ty, _) => check(ty),
+            Ty::Tuple(fields) => fields.iter().all(check),
+            Ty::Struct(fields) => fields.iter().all(|field| check(&field.ty)),
+            Ty::UnicodeChar
+            | Ty::Enum(_)
+            | Ty::Union(_)
+            | Ty::List(..)
+            | Ty::Set(..)
+            | Ty::Map(..) => false,
+        }
+    }
+
+    /// Computes minimal and maximal size of the type serialized with strict encoding. The maximal
+    /// size is `None` if it doesn't fit `usize` - like for collections with unbounded sizing.
+    ///
+    /// References which are not inline types are resolved with the `resolver`. The bounds are
+    /// conservative: unresolved and recursive references are assumed to take from zero to an
+    /// unbounded number of bytes.
+    pub fn serialized_size_bounds<'r>(
+        &self,
+        resolver: &impl Fn(&Ref) -> Option<&'r Ty<Ref>>,
+    ) -> (usize, Option<usize>)
+    where
+        Ref: 'r,
+    {
+        self.size_bounds_inner(resolver, &mut vec![])
+    }
+
+    fn size_bounds_inner<'r>(
+        &self,
+        resolver: &impl Fn(&Ref) -> Option<&'r Ty<Ref>>,
+        stack: &mut Vec<Ref>,
+    ) -> (usize, Option<usize>)
+    where
+        Ref: 'r,
+    {
+        const UNKNOWN: (usize, Option<usize>) = (0, None);
+        let mut bounds = |r: &Ref| {
+            if stack.contains(r) {
+                return UNKNOWN;
+            }
+            if let Some(ty) = r.as_ty() {
+                return ty.size_bounds_inner(resolver, stack);
+            }
+            let Some(ty) = resolver(r) else {
+                return UNKNOWN;
+            };
+            stack.push(r.clone());
+            let bounds = ty.size_bounds_inner(resolver, stack);
+            stack.pop();
+            bounds
+        };
+        let sum = |all: &mut dyn Iterator<Item = (usize, Option<usize>)>| {
+            all.fold((0usize, Some(0usize)), |(min, max), (elem_min, elem_max)| {
+                (
+                    min.saturating_add(elem_min),
+                    max.zip(elem_max).and_then(|(a, b)| a.checked_add(b)),
+                )
+            })
+        };
+        let collection = |sizing: &Sizing, (elem_min, elem_max): (usize, Option<usize>)| {
+            let prefix = sizing.byte_size();
+            let min = usize::try_from(sizing.min)
+                .unwrap_or(usize::MAX)
+                .saturating_mul(elem_min)
+                .saturating_add(prefix);
+            let max = usize::try_from(sizing.max)
+                .ok()
+                .zip(elem_max)
+                .and_then(|(len, elem_max)| len.checked_mul(elem_max))
+                .and_then(|size| size.checked_add(prefix));
+            (min, max)
+        };
+        match self {
+            Ty::Primitive(prim) => (prim.byte_size() as usize, Some(prim.byte_size() as usize)),
+            Ty::UnicodeChar => (1, Some(4)),
+            Ty::Enum(_) => (1, Some(1)),
+            Ty::Union(variants) => {
+                let mut min = usize::MAX;
+                let mut max = Some(0usize);
+                for ty in variants.values() {
+                    let (var_min, var_max) = bounds(ty);
+                    min = min.min(var_min);
+                    max = max.zip(var_max).map(|(a, b)| a.max(b));
+                }
+                (min.saturating_add(1), max.and_then(|max| max.checked_add(1)))
+            }
+            Ty::Tuple(fields) => sum(&mut fields.iter().map(bounds)),
+            Ty::Struct(fields) => sum(&mut fields.iter().map(|field| bounds(&field.ty))),
+            Ty::Array(ty, len) => {
+                let (min, max) = bounds(ty);
+                let len = *len as usize;
+                (min.saturating_mul(len), max.and_then(|max| max.checked_mul(len)))
+            }
+            Ty::List(ty, sizing) | Ty::Set(ty, sizing) => collection(sizing, bounds(ty)),
+            Ty::Map(key, ty, sizing) => {
+                let key = bounds(key);
+                let val = bounds(ty);
+                collection(sizing, sum(&mut [key, val].into_iter()))
+            }
+        }
+    }
+
+    /// Returns element type of arrays, lists and sets, or value type of maps.
+    pub fn element(&self) -> Option<&Ref> {
+        match self {
+            Ty::Array(ty, _) | Ty::List(ty, _) | Ty::Set(ty, _) | Ty::Map(_, ty, _) => Some(ty),
+            _ => None,
+        }
+    }
+
+    /// Returns key type of maps.
+    pub fn map_key(&self) -> Option<&Ref> {
+        match self {
+            Ty::Map(key, _, _) => Some(key),
+            _ => None,
+        }
+    }
+
+    /// Returns structure fields if the type is a structure.
+    pub fn as_struct(&self) -> Option<&NamedFields<Ref>> {
+        match self {
+            Ty::Struct(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Returns union variants if the type is a union.
+    pub fn as_union(&self) -> Option<&UnionVariants<Ref>> {
+        match self {
+            Ty::Union(variants) => Some(variants),
+            _ => None,
+        }
+    }
+
+    /// Returns enum variants if the type is an enum.
+    pub fn as_enum(&self) -> Option<&EnumVariants> {
+        match self {
+            Ty::Enum(variants) => Some(variants),
+            _ => None,
+        }
+    }
+
+    /// Returns tuple fields if the type is a tuple.
+    pub fn as_tuple(&self) -> Option<&UnnamedFields<Ref>> {
+        match self {
+            Ty::Tuple(fields) => Some(fields),
+            _ => None,
+        }
+    }
+
+    /// Returns minimal and maximal values of integer primitive types, or `None` for non-integer
+    /// types and integers wider than 128 bits.
+    pub fn numeric_bounds(&self) -> Option<(i128, u128)> {
+        let Ty::Primitive(prim) = self else {
+            return None;
+        };
+        if *prim == Primitive::BYTE || *prim == Primitive::UNIT {
+            return None;
+        }
+        let info = prim.info();
+        let bits = info.byte_size() as u32 * 8;
+        if bits == 0 || bits > 128 {
+            return None;
+        }
+        let shift = 128 - bits;
+        match info.ty {
+            NumCls::Unsigned => Some((0, u128::MAX >> shift)),
+            NumCls::NonZero => Some((1, u128::MAX >> shift)),
+            NumCls::Signed => Some((i128::MIN >> shift, (i128::MAX >> shift) as u128)),
+            NumCls::Float => None,
+        }
+    }
+
+    /// Compares layout of two types ignoring names of fields and variants: the types must have
+    /// the same class, primitive codes, sizing and variant tags, and all their inner types must
+    /// be equivalent as well.
+    ///
+    /// Inner types are compared recursively. References which are not inline types are resolved
+    /// with the `resolver`; if either of them can't be resolved, the references must be equal.
+    /// Recursive types are considered equivalent when the same pair of references is met again.
+    pub fn equivalent_ignoring_names<'r>(
+        &self,
+        other: &Ty<Ref>,
+        resolver: &impl Fn(&Ref) -> Option<&'r Ty<Ref>>,
+    ) -> bool
+    where
+        Ref: 'r,
+    {
+        self.equivalent_inner(other, resolver, &mut vec![])
+    }
+
+    fn equivalent_inner<'r>(
+        &self,
+        other: &Ty<Ref>,
+        resolver: &impl Fn(&Ref) -> Option<&'r Ty<Ref>>,
+        visited: &mut Vec<(Ref, Ref)>,
+    ) -> bool
+    where
+        Ref: 'r,
+    {
+        let mut check = |a: &Ref, b: &Ref| {
+            if let (Some(a), Some(b)) = (a.as_ty(), b.as_ty()) {
+                return a.equivalent_inner(b, resolver, visited);
+            }
+            let pair = (a.clone(), b.clone());
+            if visited.contains(&pair) {
+                return true;
+            }
+            let (Some(ty_a), Some(ty_b)) = (resolver(a), resolver(b)) else {
+                return a == b;
+            };
+            visited.push(pair);
+            ty_a.equivalent_inner(ty_b, resolver, visited)
+        };
+
+        match (self, other) {
+            (Ty::Primitive(a), Ty::Primitive(b)) => a == b,
+            (Ty::UnicodeChar, Ty::UnicodeChar) => true,
+            (Ty::Enum(a), Ty::Enum(b)) => a.iter().map(|v| v.tag).eq(b.iter().map(|v| v.tag)),
+            (Ty::Union(a), Ty::Union(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .zip(b.iter())
+                        .all(|((va, a), (vb, b))| va.tag == vb.tag && check(a, b))
+            }
+            (Ty::Tuple(a), Ty::Tuple(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| check(a, b))
+            }
+            (Ty::Struct(a), Ty::Struct(b)) => {
+                a.len() == b.len() && a.iter().zip(b.iter()).all(|(a, b)| check(&a.ty, &b.ty))
+            }
+            (Ty::Array(a, len_a), Ty::Array(b, len_b)) => len_a == len_b && check(a, b),
+            (Ty::List(a, sizing_a), Ty::List(b, sizing_b))
+            | (Ty::Set(a, sizing_a), Ty::Set(b, sizing_b)) => sizing_a == sizing_b && check(a, b),
+            (Ty::Map(ka, va, sizing_a), Ty::Map(kb, vb, sizing_b)) => {
+                sizing_a == sizing_b && check(ka, kb) && check(va, vb)
+            }
+            _ => false,
+        }
+    }
 
     pub fn as_wrapped_ty(&self) -> Option<&Ty<Ref>> {
         if let Ty::Tuple(fields) = self {
@@ -246,6 +752,115 @@ impl<Ref: TypeRef> Ty<Ref> {
     }
 }
 
+/// Variants are compared by both their tags and names, since [`Variant`] equality requires only
+/// one of them to match.
+impl<Ref: TypeRef> PartialEq for Ty<Ref> {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Ty::Primitive(a), Ty::Primitive(b)) => a == b,
+            (Ty::UnicodeChar, Ty::UnicodeChar) => true,
+            (Ty::Enum(a), Ty::Enum(b)) => {
+                let a = a.iter().map(|v| (v.tag, &v.name));
+                a.eq(b.iter().map(|v| (v.tag, &v.name)))
+            }
+            (Ty::Union(a), Ty::Union(b)) => {
+                let a = a.iter().map(|(v, ty)| (v.tag, &v.name, ty));
+                a.eq(b.iter().map(|(v, ty)| (v.tag, &v.name, ty)))
+            }
+            (Ty::Tuple(a), Ty::Tuple(b)) => a == b,
+            (Ty::Struct(a), Ty::Struct(b)) => a == b,
+            (Ty::Array(a, len_a), Ty::Array(b, len_b)) => a == b && len_a == len_b,
+            (Ty::List(a, sa), Ty::List(b, sb)) | (Ty::Set(a, sa), Ty::Set(b, sb)) => {
+                a == b && sa == sb
+            }
+            (Ty::Map(ka, a, sa), Ty::Map(kb, b, sb)) => ka == kb && a == b && sa == sb,
+            _ => false,
+        }
+    }
+}
+
+impl<Ref: TypeRef + Ord> PartialOrd for Ty<Ref> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> { Some(self.cmp(other)) }
+}
+
+/// Types are ordered by their class first, and then by their structural content. Variants are
+/// compared by both their tags and names, consistently with the type equality.
+impl<Ref: TypeRef + Ord> Ord for Ty<Ref> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self, other) {
+            (Ty::Primitive(a), Ty::Primitive(b)) => a.cmp(b),
+            (Ty::UnicodeChar, Ty::UnicodeChar) => Ordering::Equal,
+            (Ty::Enum(a), Ty::Enum(b)) => {
+                let a = a.iter().map(|v| (v.tag, &v.name));
+                a.cmp(b.iter().map(|v| (v.tag, &v.name)))
+            }
+            (Ty::Union(a), Ty::Union(b)) => {
+                let a = a.iter().map(|(v, ty)| (v.tag, &v.name, ty));
+                a.cmp(b.iter().map(|(v, ty)| (v.tag, &v.name, ty)))
+            }
+            (Ty::Tuple(a), Ty::Tuple(b)) => a.cmp(b),
+            (Ty::Struct(a), Ty::Struct(b)) => a.cmp(b),
+            (Ty::Array(a, len_a), Ty::Array(b, len_b)) => (a, len_a).cmp(&(b, len_b)),
+            (Ty::List(a, sa), Ty::List(b, sb)) | (Ty::Set(a, sa), Ty::Set(b, sb)) => {
+                (a, sa.min, sa.max).cmp(&(b, sb.min, sb.max))
+            }
+            (Ty::Map(ka, a, sa), Ty::Map(kb, b, sb)) => {
+                (ka, a, sa.min, sa.max).cmp(&(kb, b, sb.min, sb.max))
+            }
+            _ => self.cls().cmp(&other.cls()),
+        }
+    }
+}
+
+impl<Ref: PrimitiveRef> Ty<Ref> {
+    /// Constructs fixed-size byte array type, like `[Byte ^ 32]`.
+    pub fn byte_array(len: u16) -> Self { Ty::Array(Ref::byte(), len) }
+    /// Constructs variable-length byte string type.
+    pub fn bytes(sizing: Sizing) -> Self { Ty::List(Ref::byte(), sizing) }
+}
+
+impl<Ref: TypeRef> Ty<Ref> {
+    /// Constructs option type, which is a union of `none` variant with tag 0 containing the
+    /// `unit` type and `some` variant with tag 1 containing the `inner` type.
+    pub(crate) fn option_with(unit: Ref, inner: Ref) -> Self {
+        Ty::Union(UnionVariants::from(Confined::from_checked(bmap! {
+            Variant::none() => unit,
+            Variant::some() => inner,
+        })))
+    }
+}
+
+/// Cheap structural hash of the type, which doesn't require computing its semantic id.
+impl<Ref: TypeRef + Hash> Hash for Ty<Ref> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.cls().hash(state);
+        match self {
+            Ty::Primitive(prim) => prim.hash(state),
+            Ty::UnicodeChar => {}
+            Ty::Enum(variants) => variants.iter().for_each(|v| v.hash(state)),
+            Ty::Union(variants) => variants.iter().for_each(|v| v.hash(state)),
+            Ty::Tuple(fields) => fields.iter().for_each(|ty| ty.hash(state)),
+            Ty::Struct(fields) => fields.iter().for_each(|field| {
+                field.name.hash(state);
+                field.ty.hash(state);
+            }),
+            Ty::Array(ty, len) => {
+                ty.hash(state);
+                len.hash(state);
+            }
+            Ty::List(ty, sizing) | Ty::Set(ty, sizing) => {
+                ty.hash(state);
+                sizing.hash(state);
+            }
+            Ty::Map(key, ty, sizing) => {
+                key.hash(state);
+                ty.hash(state);
+                sizing.hash(state);
+            }
+        }
+    }
+}
+
 impl<Ref: TypeRef> Display for Ty<Ref>
 where Ref: Display
 {
@@ -309,6 +924,9 @@ pub enum ItemCase {
 }
 
 impl<Ref: TypeRef> Ty<Ref> {
+    /// Returns type of the item at the position `pos`. For unions this is the variant at the
+    /// position, which is different from the variant with the tag `pos` when union tags are not
+    /// contiguous; use [`Ty::ty_for_tag`] to resolve a variant by its tag.
     pub fn ty_at(&self, pos: u8) -> Option<&Ref> {
         match self {
             Ty::Union(fields) => fields.ty_by_pos(pos),
@@ -321,6 +939,15 @@ impl<Ref: TypeRef> Ty<Ref> {
             _ => None,
         }
     }
+
+    /// Returns type of the union variant with the given tag, as encoded on the wire, or `None`
+    /// for non-union types and unknown tags.
+    pub fn ty_for_tag(&self, tag: u8) -> Option<&Ref> {
+        match self {
+            Ty::Union(variants) => variants.ty_by_tag(tag),
+            _ => None,
+        }
+    }
     pub fn case_at(&self, pos: u8) -> Option<ItemCase> {
         match self {
             Ty::Union(fields) => {
@@ -413,6 +1040,9 @@ impl<'a, Ref: TypeRef> IntoIterator for &'a NamedFields<Ref> {
 impl<Ref: TypeRef> NamedFields<Ref> {
     pub fn into_inner(self) -> Vec<Field<Ref>> { self.0.release() }
 
+    pub fn remaining_capacity(&self) -> usize { u8::MAX as usize - self.0.len() }
+    pub fn is_full(&self) -> bool { self.remaining_capacity() == 0 }
+
     pub fn ty_by_pos(&self, pos: u8) -> Option<&Ref> { self.0.get(pos as usize).map(|f| &f.ty) }
     pub fn ty_by_name(&self, name: &FieldName) -> Option<&Ref> {
         self.0.iter().find(|f| &f.name == name).map(|f| &f.ty)
@@ -492,6 +1122,9 @@ impl<'a, Ref: TypeRef> IntoIterator for &'a UnnamedFields<Ref> {
 impl<Ref: TypeRef> UnnamedFields<Ref> {
     pub fn into_inner(self) -> Vec<Ref> { self.0.release() }
 
+    pub fn remaining_capacity(&self) -> usize { u8::MAX as usize - self.0.len() }
+    pub fn is_full(&self) -> bool { self.remaining_capacity() == 0 }
+
     pub fn ty_by_pos(&self, pos: u8) -> Option<&Ref> { self.0.get(pos as usize) }
 }
 
@@ -560,9 +1193,55 @@ impl<'a, Ref: TypeRef> IntoIterator for &'a UnionVariants<Ref> {
     fn into_iter(self) -> Self::IntoIter { self.0.iter() }
 }
 
+/// Error constructing array type which can't be serialized.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(
+    "collection of {len} elements of {elem_size} bytes each exceeds maximal strict encoding size \
+     of 2^24 bytes"
+)]
+pub struct SizingError {
+    pub elem_size: usize,
+    pub len: u64,
+}
+
+/// Type normalized with respect to being an option, returned by [`Ty::normalize_option`].
+#[derive(Copy, Clone, Eq, PartialEq, Debug)]
+pub enum OptionNorm<'ty, Ref: TypeRef> {
+    /// Option type, with the reference to the type wrapped into the option.
+    Optional(&'ty Ref),
+    /// Any other type.
+    Plain(&'ty Ty<Ref>),
+}
+
+/// Error remapping union variant tags.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display("tag remapping results in multiple union variants having the same tag {0}")]
+pub struct TagError(pub u8);
+
 impl<Ref: TypeRef> UnionVariants<Ref> {
+    /// Renumbers variant tags by applying `mapping` to each of them, keeping variant names and
+    /// types. Fails if two variants receive the same tag after the remapping.
+    ///
+    /// NB: Variant tags are committed to by the type semantic id, thus a remapped union will
+    /// have a different [`SemId`](crate::SemId).
+    pub fn remap_tags(self, mapping: impl Fn(u8) -> u8) -> Result<Self, TagError> {
+        let mut tags = BTreeSet::new();
+        let mut variants = BTreeMap::new();
+        for (variant, ty) in self.0.release() {
+            let tag = mapping(variant.tag);
+            if !tags.insert(tag) {
+                return Err(TagError(tag));
+            }
+            variants.insert(Variant::named(tag, variant.name), ty);
+        }
+        Ok(Self(Confined::from_checked(variants)))
+    }
+
     pub fn into_inner(self) -> BTreeMap<Variant, Ref> { self.0.release() }
 
+    pub fn remaining_capacity(&self) -> usize { u8::MAX as usize - self.0.len() }
+    pub fn is_full(&self) -> bool { self.remaining_capacity() == 0 }
+
     pub fn unwrap_first(&self) -> &Variant { self.0.first_key_value().unwrap().0 }
     pub fn unwrap_last(&self) -> &Variant { self.0.last_key_value().unwrap().0 }
 
@@ -574,6 +1253,11 @@ impl<Ref: TypeRef> UnionVariants<Ref> {
         self.0.release().into_values()
     }
 
+    /// Iterates over variants in the order of their tags, which is the order of the union
+    /// discriminants in the strict encoding. This is the order of the underlying map, since
+    /// [`Variant`] ordering compares variant tags.
+    pub fn iter_by_tag(&self) -> impl Iterator<Item = (&Variant, &Ref)> { self.0.iter() }
+
     pub fn has_tag(&self, tag: u8) -> bool { self.0.keys().any(|v| v.tag == tag) }
     pub fn by_tag(&self, tag: u8) -> Option<(&Variant, &Ref)> {
         self.0.iter().find(|(v, _)| v.tag == tag)
@@ -680,6 +1364,9 @@ impl<'a> IntoIterator for &'a EnumVariants {
 impl EnumVariants {
     pub fn into_inner(self) -> BTreeSet<Variant> { self.0.release() }
 
+    pub fn remaining_capacity(&self) -> usize { u8::MAX as usize - self.0.len() }
+    pub fn is_full(&self) -> bool { self.remaining_capacity() == 0 }
+
     pub fn tag_by_name(&self, name: &VariantName) -> Option<u8> {
         self.0.iter().find(|v| &v.name == name).map(|v| v.tag)
     }
@@ -725,3 +1412,444 @@ impl Display for EnumVariants {
         writeln!(f)
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SemId;
+
+    #[test]
+    fn kind_str() {
+        assert_eq!(Ty::<SemId>::U32.kind_str(), "u32");
+        assert_eq!(Ty::<SemId>::UNICODE.kind_str(), "unicode");
+
+        let option =
+            Ty::<SemId>::union(variants!("none" => SemId::unit(), "some" => SemId::byte()));
+        assert!(option.is_option());
+        assert_eq!(option.kind_str(), "option");
+
+        let union = Ty::<SemId>::union(variants!(
+            "first" => SemId::unit(),
+            "second" => SemId::byte(),
+            "third" => SemId::unicode_char(),
+        ));
+        assert_eq!(union.kind_str(), "union");
+    }
+
+    #[test]
+    fn option_inner() {
+        let option =
+            Ty::<SemId>::union(variants!("none" => SemId::unit(), "some" => SemId::byte()));
+        assert_eq!(option.option_inner(), Some(&SemId::byte()));
+
+        let either =
+            Ty::<SemId>::union(variants!("left" => SemId::unit(), "right" => SemId::byte()));
+        assert_eq!(either.option_inner(), None);
+    }
+
+    #[test]
+    fn newtype_inner() {
+        let tuple = Ty::<SemId>::Tuple(fields!(SemId::byte()));
+        assert_eq!(tuple.newtype_inner(), Some(&SemId::byte()));
+
+        let single = Ty::<SemId>::Struct(fields!("inner" => SemId::byte()));
+        assert_eq!(single.newtype_inner(), Some(&SemId::byte()));
+
+        let pair =
+            Ty::<SemId>::Struct(fields!("first" => SemId::byte(), "second" => SemId::unit()));
+        assert_eq!(pair.newtype_inner(), None);
+    }
+
+    #[test]
+    fn equivalent_ignoring_names() {
+        let none = |_: &SemId| None;
+        let ty = Ty::<SemId>::Struct(fields!("first" => SemId::byte(), "second" => SemId::unit()));
+        let renamed = Ty::<SemId>::Struct(fields!("one" => SemId::byte(), "two" => SemId::unit()));
+        assert!(ty.equivalent_ignoring_names(&renamed, &none));
+
+        let changed =
+            Ty::<SemId>::Struct(fields!("first" => SemId::byte(), "second" => SemId::byte()));
+        assert!(!ty.equivalent_ignoring_names(&changed, &none));
+    }
+
+    #[test]
+    fn equivalent_ignoring_nested_names() {
+        let inner =
+            Ty::<SemId>::Struct(fields!("first" => SemId::byte(), "second" => SemId::unit()));
+        let renamed = Ty::<SemId>::Struct(fields!("one" => SemId::byte(), "two" => SemId::unit()));
+        let changed =
+            Ty::<SemId>::Struct(fields!("first" => SemId::byte(), "second" => SemId::byte()));
+        let outer = |ty: &Ty<SemId>| Ty::<SemId>::List(ty.sem_id_unnamed(), Sizing::U8);
+        let outers = [&inner, &renamed, &changed].map(outer);
+        let types = [inner, renamed, changed]
+            .into_iter()
+            .map(|ty| (ty.sem_id_unnamed(), ty))
+            .collect::<BTreeMap<_, _>>();
+        let resolver = |id: &SemId| types.get(id);
+
+        let [inner, renamed, changed] = outers;
+        assert!(inner.equivalent_ignoring_names(&renamed, &resolver));
+        assert!(!inner.equivalent_ignoring_names(&changed, &resolver));
+        // Without resolving references the nested names are still committed to by the ids
+        assert!(!inner.equivalent_ignoring_names(&renamed, &|_| None));
+    }
+
+    #[test]
+    fn cls_error() {
+        assert_eq!(Cls::from_byte(10), Ok(Cls::Map));
+        assert_eq!(
+            Cls::from_byte(42).unwrap_err().to_string(),
+            "invalid Cls byte 42, expected 0..=10"
+        );
+    }
+
+    #[test]
+    fn cls_groups() {
+        let groups = |cls: Cls| (cls.is_scalar(), cls.is_composite(), cls.is_collection());
+        assert_eq!(groups(Cls::Primitive), (true, false, false));
+        assert_eq!(groups(Cls::Unicode), (true, false, false));
+        assert_eq!(groups(Cls::AsciiStr), (true, false, false));
+        assert_eq!(groups(Cls::Enum), (false, true, false));
+        assert_eq!(groups(Cls::Union), (false, true, false));
+        assert_eq!(groups(Cls::Struct), (false, true, false));
+        assert_eq!(groups(Cls::Tuple), (false, true, false));
+        assert_eq!(groups(Cls::Array), (false, false, true));
+        assert_eq!(groups(Cls::List), (false, false, true));
+        assert_eq!(groups(Cls::Set), (false, false, true));
+        assert_eq!(groups(Cls::Map), (false, false, true));
+    }
+
+    #[test]
+    fn cls_strict_round_trip() {
+        use strict_encoding::{StrictReader, StrictWriter};
+
+        for cls in Cls::ALL {
+            let writer = cls.strict_encode(StrictWriter::in_memory::<1>()).unwrap();
+            let data = writer.unbox().unconfine();
+            assert_eq!(data, vec![cls as u8]);
+            let mut reader = StrictReader::in_memory::<1>(data);
+            assert_eq!(Cls::strict_decode(&mut reader).unwrap(), cls);
+        }
+        let mut reader = StrictReader::in_memory::<1>(vec![11u8]);
+        assert!(Cls::strict_decode(&mut reader).is_err());
+    }
+
+    #[test]
+    fn as_primitive() {
+        assert_eq!(Ty::<SemId>::U32.as_primitive(), Some(Primitive::U32));
+        assert_eq!(Ty::<SemId>::UNICODE.as_primitive(), None);
+        let ty = Ty::<SemId>::Struct(fields!("value" => SemId::byte()));
+        assert_eq!(ty.as_primitive(), None);
+    }
+
+    #[test]
+    fn as_compound() {
+        let strct = Ty::<SemId>::Struct(fields!("value" => SemId::byte()));
+        let tuple = Ty::<SemId>::Tuple(fields!(SemId::byte()));
+        let union = Ty::<SemId>::Union(variants!("a" => SemId::byte()));
+        let enm = Ty::<SemId>::Enum(variants!("a", "b"));
+        assert!(strct.as_struct().is_some() && tuple.as_struct().is_none());
+        assert!(tuple.as_tuple().is_some() && strct.as_tuple().is_none());
+        assert!(union.as_union().is_some() && enm.as_union().is_none());
+        assert!(enm.as_enum().is_some() && union.as_enum().is_none());
+    }
+
+    #[test]
+    fn ordering() {
+        let list = Ty::<SemId>::List(SemId::byte(), Sizing::U8);
+        let strct = Ty::<SemId>::Struct(fields!("value" => SemId::byte()));
+        let sorted = vec![Ty::U8, Ty::U32, Ty::UNICODE, strct.clone(), list.clone(), list.clone()];
+        let mut shuffled = vec![list.clone(), Ty::U32, strct, list, Ty::UNICODE, Ty::U8];
+        shuffled.sort();
+        assert_eq!(shuffled, sorted);
+        let mut reversed = sorted.clone();
+        reversed.reverse();
+        reversed.sort();
+        assert_eq!(reversed, sorted);
+    }
+
+    #[test]
+    fn ordering_variant_names() {
+        let a = Ty::<SemId>::Enum(variants!("first", "second"));
+        let b = Ty::<SemId>::Enum(variants!("first", "other"));
+        assert_ne!(a, b);
+        assert_ne!(a.cmp(&b), Ordering::Equal);
+
+        let c = Ty::<SemId>::Union(variants!("first" => SemId::unit(), "second" => SemId::byte()));
+        let d = Ty::<SemId>::Union(variants!("first" => SemId::unit(), "other" => SemId::byte()));
+        assert_ne!(c, d);
+        assert_ne!(c.cmp(&d), Ordering::Equal);
+
+        assert_eq!(bset![a, b, c, d].len(), 4);
+    }
+
+    #[test]
+    fn hashing() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash(ty: &Ty<SemId>) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            ty.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let a = Ty::<SemId>::Struct(fields!("value" => SemId::byte()));
+        let b = Ty::<SemId>::Struct(fields!("value" => SemId::byte()));
+        let renamed = Ty::<SemId>::Struct(fields!("other" => SemId::byte()));
+        assert_eq!(hash(&a), hash(&b));
+        assert_ne!(hash(&a), hash(&renamed));
+        let list = Ty::<SemId>::List(SemId::byte(), Sizing::U8);
+        let set = Ty::<SemId>::Set(SemId::byte(), Sizing::U8);
+        assert_ne!(hash(&list), hash(&set));
+    }
+
+    #[test]
+    fn array_sizing() {
+        let ty = Ty::<SemId>::array(SemId::byte(), u16::MAX, |_| 256).unwrap();
+        assert_eq!(ty, Ty::Array(SemId::byte(), u16::MAX));
+        assert_eq!(
+            Ty::<SemId>::array(SemId::byte(), u16::MAX, |_| 1024),
+            Err(SizingError {
+                elem_size: 1024,
+                len: u16::MAX as u64
+            })
+        );
+    }
+
+    #[test]
+    fn serialized_size_bounds() {
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let u32 = Ty::<SemId>::U32.sem_id_unnamed();
+        let option = Ty::<SemId>::option(u8);
+        let list = Ty::<SemId>::List(u32, Sizing::new(1, 10));
+        let unbounded = Ty::<SemId>::List(u32, Sizing::new(0, u64::MAX));
+        let types = bmap! {
+            SemId::unit() => Ty::UNIT,
+            u8 => Ty::U8,
+            u32 => Ty::U32,
+            option.sem_id_unnamed() => option.clone(),
+            list.sem_id_unnamed() => list.clone(),
+        };
+        let resolver = |id: &SemId| types.get(id);
+
+        let strct = Ty::<SemId>::Struct(fields!("a" => u32, "b" => option.sem_id_unnamed()));
+        assert_eq!(strct.serialized_size_bounds(&resolver), (5, Some(6)));
+        assert_eq!(list.serialized_size_bounds(&resolver), (5, Some(41)));
+        assert_eq!(unbounded.serialized_size_bounds(&resolver), (8, None));
+        let map = Ty::<SemId>::Map(u8, list.sem_id_unnamed(), Sizing::U8);
+        assert_eq!(map.serialized_size_bounds(&resolver), (1, Some(1 + 255 * 42)));
+        assert_eq!(Ty::<SemId>::Array(u32, 4).serialized_size_bounds(&resolver), (16, Some(16)));
+        assert_eq!(
+            Ty::<SemId>::Array(list.sem_id_unnamed(), 2).serialized_size_bounds(&|_| None),
+            (0, None)
+        );
+    }
+
+    #[test]
+    fn primitive_name() {
+        assert_eq!(Ty::<SemId>::U32.primitive_name(), Some(s!("U32")));
+        assert_eq!(Ty::<SemId>::UNIT.primitive_name(), Some(s!("Unit")));
+        assert_eq!(Ty::<SemId>::UNICODE.primitive_name(), Some(s!("Unicode")));
+        assert_eq!(Ty::<SemId>::bytes(Sizing::U8).primitive_name(), None);
+    }
+
+    #[test]
+    fn simplify() {
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let single = Ty::<SemId>::Union(variants!("only" => u8));
+        let single_id = single.sem_id_unnamed();
+        let simple = single.simplify();
+        assert_eq!(simple, Ty::Tuple(UnnamedFields::try_from(vec![u8]).unwrap()));
+        assert_ne!(simple.sem_id_unnamed(), single_id);
+
+        let union = Ty::<SemId>::Union(variants!("a" => u8, "b" => SemId::unit()));
+        assert_eq!(union.clone().simplify(), union);
+        let option = Ty::<SemId>::option(u8);
+        assert_eq!(option.clone().simplify(), option);
+    }
+
+    #[test]
+    fn option() {
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let option = Ty::<SemId>::option(u8);
+        assert!(option.is_option());
+        assert_eq!(option.option_inner(), Some(&u8));
+        assert_eq!(option, Ty::Union(variants!("none" => SemId::unit(), "some" => u8)));
+    }
+
+    #[test]
+    fn normalize_option() {
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let option = Ty::<SemId>::Union(variants!("none" => SemId::unit(), "some" => u8));
+        assert_eq!(option.normalize_option(), OptionNorm::Optional(&u8));
+        let union = Ty::<SemId>::Union(variants!("none" => SemId::unit(), "other" => u8));
+        assert_eq!(union.normalize_option(), OptionNorm::Plain(&union));
+        assert_eq!(Ty::<SemId>::U8.normalize_option(), OptionNorm::Plain(&Ty::U8));
+    }
+
+    #[test]
+    fn zero_sized() {
+        let resolver = |_: &SemId| None;
+        assert!(Ty::<SemId>::UNIT.is_zero_sized(&resolver));
+        assert!(!Ty::<SemId>::U8.is_zero_sized(&resolver));
+        assert!(Ty::<SemId>::Array(SemId::byte(), 0).is_zero_sized(&resolver));
+
+        let unit = SemId::unit();
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let types = bmap! { unit => Ty::UNIT, u8 => Ty::U8 };
+        let resolver = |id: &SemId| types.get(id);
+        assert!(Ty::<SemId>::Struct(fields!("a" => unit, "b" => unit)).is_zero_sized(&resolver));
+        assert!(!Ty::<SemId>::Struct(fields!("a" => unit, "b" => u8)).is_zero_sized(&resolver));
+        assert!(Ty::<SemId>::Array(unit, 16).is_zero_sized(&resolver));
+        assert!(!Ty::<SemId>::List(unit, Sizing::U8).is_zero_sized(&resolver));
+    }
+
+    #[test]
+    fn map_sizing() {
+        let key = SemId::byte();
+        let val = Ty::<SemId>::U64.sem_id_unnamed();
+        let ty = Ty::<SemId>::map_checked(key, val, Sizing::U16, 1, 8).unwrap();
+        assert_eq!(ty, Ty::Map(key, val, Sizing::U16));
+        assert_eq!(
+            Ty::<SemId>::map_checked(key, val, Sizing::U16, 32, 256),
+            Err(SizingError {
+                elem_size: 288,
+                len: u16::MAX as u64
+            })
+        );
+    }
+
+    #[test]
+    fn byte_array() {
+        let ty = Ty::<SemId>::byte_array(32);
+        assert_eq!(ty, Ty::Array(SemId::byte(), 32));
+        assert!(ty.is_byte_array());
+        assert_eq!(Ty::<SemId>::bytes(Sizing::U16), Ty::List(SemId::byte(), Sizing::U16));
+    }
+
+    #[test]
+    fn contains_ref() {
+        let strct = Ty::<SemId>::Struct(fields!("value" => SemId::byte()));
+        assert!(strct.contains_ref(&SemId::byte()));
+        assert!(!strct.contains_ref(&SemId::unit()));
+        assert!(!Ty::<SemId>::U8.contains_ref(&SemId::byte()));
+    }
+
+    #[test]
+    fn element() {
+        let byte = SemId::byte();
+        let unit = SemId::unit();
+        assert_eq!(Ty::<SemId>::Array(byte, 4).element(), Some(&byte));
+        assert_eq!(Ty::<SemId>::List(byte, Sizing::U8).element(), Some(&byte));
+        assert_eq!(Ty::<SemId>::Set(byte, Sizing::U8).element(), Some(&byte));
+        let map = Ty::<SemId>::Map(unit, byte, Sizing::U8);
+        assert_eq!(map.element(), Some(&byte));
+        assert_eq!(map.map_key(), Some(&unit));
+        assert_eq!(Ty::<SemId>::List(byte, Sizing::U8).map_key(), None);
+        assert_eq!(Ty::<SemId>::U8.element(), None);
+    }
+
+    #[test]
+    fn remap_tags() {
+        let union: UnionVariants<SemId> = variants!("a" => SemId::byte(), "b" => SemId::unit());
+        let shifted = union.clone().remap_tags(|tag| tag + 1).unwrap();
+        assert_eq!(shifted.tag_by_name(&vname!("a")), Some(1));
+        assert_eq!(shifted.tag_by_name(&vname!("b")), Some(2));
+        assert_eq!(shifted.ty_by_tag(1), Some(&SemId::byte()));
+        assert_eq!(union.remap_tags(|_| 5), Err(TagError(5)));
+    }
+
+    #[test]
+    fn iter_by_tag() {
+        let union = UnionVariants::try_from(bmap! {
+            Variant::named(7, vname!("alpha")) => SemId::unit(),
+            Variant::named(2, vname!("zeta")) => SemId::byte(),
+            Variant::named(4, vname!("beta")) => SemId::unit(),
+        })
+        .unwrap();
+        let tags = union.iter_by_tag().map(|(v, _)| v.tag).collect::<Vec<_>>();
+        assert_eq!(tags, vec![2, 4, 7]);
+        let (first, ty) = union.iter_by_tag().next().unwrap();
+        assert_eq!(first.name, vname!("zeta"));
+        assert_eq!(ty, &SemId::byte());
+    }
+
+    #[test]
+    fn ty_for_tag() {
+        let ty = Ty::Union(
+            UnionVariants::try_from(bmap! {
+                Variant::named(0, vname!("zeta")) => SemId::unit(),
+                Variant::named(5, vname!("alpha")) => SemId::byte(),
+            })
+            .unwrap(),
+        );
+        assert_eq!(ty.ty_for_tag(5), Some(&SemId::byte()));
+        assert_eq!(ty.ty_for_tag(0), Some(&SemId::unit()));
+        assert_eq!(ty.ty_for_tag(1), None);
+        assert_eq!(ty.ty_at(1), Some(&SemId::byte()));
+        assert_eq!(Ty::<SemId>::List(SemId::byte(), Sizing::U8).ty_for_tag(0), None);
+    }
+
+    #[test]
+    fn enum_lookup() {
+        let variants = EnumVariants::try_from(bset! {
+            Variant::named(3, vname!("red")),
+            Variant::named(10, vname!("green")),
+            Variant::named(200, vname!("blue")),
+        })
+        .unwrap();
+        assert_eq!(variants.by_tag(10), Some(&Variant::named(10, vname!("green"))));
+        assert_eq!(variants.by_tag(4), None);
+        assert_eq!(variants.by_name(&vname!("blue")), Some(&Variant::named(200, vname!("blue"))));
+        assert_eq!(variants.by_name(&vname!("yellow")), None);
+    }
+
+    #[test]
+    fn numeric_bounds() {
+        assert_eq!(Ty::<SemId>::U16.numeric_bounds(), Some((0, 65535)));
+        assert_eq!(Ty::<SemId>::I8.numeric_bounds(), Some((-128, 127)));
+        assert_eq!(Ty::<SemId>::I128.numeric_bounds(), Some((i128::MIN, i128::MAX as u128)));
+        assert_eq!(Ty::<SemId>::U256.numeric_bounds(), None);
+        assert_eq!(Ty::<SemId>::F32.numeric_bounds(), None);
+        assert_eq!(Ty::<SemId>::BYTE.numeric_bounds(), None);
+    }
+
+    #[test]
+    fn capacity() {
+        let variants: EnumVariants = variants!(0..=253);
+        assert_eq!(variants.remaining_capacity(), 1);
+        assert!(!variants.is_full());
+        let variants: EnumVariants = variants!(0..=254);
+        assert_eq!(variants.len(), 255);
+        assert!(variants.is_full());
+
+        let fields: UnnamedFields<SemId> = fields!(SemId::unit());
+        assert_eq!(fields.remaining_capacity(), 254);
+        assert!(!fields.is_full());
+    }
+
+    #[test]
+    fn bit_flags() {
+        let enumerate = |tags: [u8; 4]| {
+            let variants = tags
+                .into_iter()
+                .map(|tag| Variant::named(tag, vname!(format!("_{tag}"))))
+                .collect::<BTreeSet<_>>();
+            Ty::<SemId>::Enum(EnumVariants::try_from(variants).unwrap())
+        };
+        assert!(enumerate([1, 2, 4, 8]).is_bit_flags());
+        assert!(!enumerate([0, 1, 2, 3]).is_bit_flags());
+    }
+
+    #[test]
+    fn numeric_enum() {
+        assert!(Ty::<SemId>::Enum(variants!(0..=15)).is_numeric_enum());
+        assert!(!Ty::<SemId>::Enum(variants!("zero", "one")).is_numeric_enum());
+
+        assert_eq!(Ty::<SemId>::Enum(variants!(3..=7)).contiguous_tags(), Some((3, 7)));
+        let gapped = bset! { Variant::named(1, vname!("a")), Variant::named(3, vname!("b")) };
+        let gapped = Ty::<SemId>::Enum(EnumVariants::try_from(gapped).unwrap());
+        assert_eq!(gapped.contiguous_tags(), None);
+        assert_eq!(Ty::<SemId>::U8.contiguous_tags(), None);
+    }
+}
@@ -26,7 +26,7 @@ use std::fmt::{Display, Formatter};
 use amplify::confinement::LargeVec;
 use strict_encoding::STRICT_TYPES_LIB;
 
-use super::vesper::TypeVesper;
+use super::vesper::{Annotations, Attr, Pred, TypeVesper};
 use crate::typesys::{TypeInfo, TypeTree};
 
 #[derive(Clone, Eq, PartialEq, Debug)]
@@ -61,11 +61,15 @@ impl Display for MemoryLayout {
 impl MemoryLayout {
     fn new() -> Self { Self { items: empty!() } }
 
-    pub fn to_vesper(&self) -> TypeVesper {
+    pub fn to_vesper(&self) -> TypeVesper { self.to_vesper_annotated(&none!()) }
+
+    /// Generates Vesper description of the layout, adding attributes from the
+    /// provided annotations.
+    pub fn to_vesper_annotated(&self, annotations: &Annotations) -> TypeVesper {
         let mut root = None;
         let mut path: Vec<usize> = vec![];
         for item in &self.items {
-            let expr = item.to_vesper();
+            let expr = item.to_vesper(annotations);
             let depth = item.depth;
 
             if path.is_empty() && depth == 0 {
@@ -89,6 +93,11 @@ impl MemoryLayout {
             for el in &path {
                 head = head.content.get_mut(*el).expect("algorithm inconsistency");
             }
+            if head.predicate == Pred::Map && expr.subject.as_str() == "key" {
+                head.attributes
+                    .push(Attr::KeyType(key_desc(&expr)))
+                    .expect("invalid type layout containing too much attributes");
+            }
             path.push(head.content.len());
             head.content
                 .push(Box::new(expr))
@@ -97,3 +106,81 @@ impl MemoryLayout {
         root.expect("invalid type layout with zero items")
     }
 }
+
+fn key_desc(key: &TypeVesper) -> String {
+    let mut desc = key
+        .attributes
+        .iter()
+        .find_map(|attr| match attr {
+            Attr::TypeName(name) => Some(name.to_string()),
+            _ => None,
+        })
+        .unwrap_or_else(|| key.predicate.to_string());
+    for attr in &key.attributes {
+        match attr {
+            Attr::Len(len) => desc.push_str(&format!(" {len}")),
+            Attr::LenRange(range) => desc.push_str(&format!(" {range}")),
+            _ => {}
+        }
+    }
+    desc
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use encoding::Sizing;
+
+    use super::*;
+    use crate::layout::vesper::Annotations;
+    use crate::typesys::{SymTy, TypeFqn};
+    use crate::{PrimitiveRef, SemId, SymbolicSys, Ty};
+
+    #[test]
+    fn map_key_type() {
+        let char = Ty::<SemId>::Enum(variants!(32..=126));
+        let char_id = char.sem_id_unnamed();
+        let key = Ty::<SemId>::List(char_id, Sizing::new(1, 32));
+        let key_id = key.sem_id_unnamed();
+        let map = Ty::<SemId>::Map(key_id, SemId::byte(), Sizing::U8);
+        let map_id = map.sem_id_unnamed();
+        let types = [char, key, Ty::BYTE, map]
+            .into_iter()
+            .map(|ty| (ty.sem_id_unnamed(), SymTy::unnamed(ty)))
+            .collect::<BTreeMap<_, _>>();
+        let sys = SymbolicSys::with(None, types).unwrap();
+
+        let vesper = sys.type_tree(map_id).unwrap().to_layout().to_vesper();
+        assert!(vesper.attributes.contains(&Attr::KeyType(s!("ascii 1..32"))));
+        assert!(vesper.display().to_string().contains("key=[ascii 1..32]"));
+    }
+
+    #[test]
+    fn default_annotation() {
+        let fqn = TypeFqn::with(libname!("Test"), tn!("Retries"));
+        let retries = Ty::<SemId>::Struct(fields!("count" => SemId::byte()));
+        let retries_id = retries.sem_id_unnamed();
+        let config = Ty::<SemId>::Struct(fields!("retries" => retries_id));
+        let config_id = config.sem_id_unnamed();
+        let types = bmap! {
+            SemId::byte() => SymTy::unnamed(Ty::BYTE),
+            retries_id => SymTy::with(Some(fqn.clone()), retries),
+            config_id => SymTy::unnamed(config),
+        };
+        let sys = SymbolicSys::with(None, types).unwrap();
+        let layout = sys.type_tree(config_id).unwrap().to_layout();
+
+        let annotations = Annotations {
+            defaults: bmap! { fqn => s!("3") },
+            ..none!()
+        };
+        let vesper = layout.to_vesper_annotated(&annotations);
+        let field = &vesper.content[0];
+        assert!(field.attributes.contains(&Attr::Default(s!("3"))));
+        assert!(vesper.display().to_string().contains("default=[3]"));
+
+        let plain = layout.to_vesper();
+        assert!(!plain.display().to_string().contains("default="));
+    }
+}
@@ -23,5 +23,7 @@
 pub mod vesper;
 mod translate;
 mod memory;
+mod parse;
 
 pub use memory::MemoryLayout;
+pub use parse::{parse_vesper, VesperParseError};
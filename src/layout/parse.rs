@@ -0,0 +1,374 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Parser for the textual form of Vesper type layouts, as produced by [`TypeVesper::display`].
+
+use std::str::FromStr;
+
+use amplify::confinement::Confined;
+use amplify::num::u24;
+use encoding::{Ident, Sizing};
+
+use super::vesper::{Attr, LenRange, Pred, TypeVesper};
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum VesperParseError {
+    /// line {0} has invalid indentation.
+    Indent(usize),
+
+    /// line {0} lacks type predicate.
+    NoPredicate(usize),
+
+    /// line {line} contains invalid identifier `{token}`.
+    InvalidIdent { line: usize, token: String },
+
+    /// line {line} contains unknown predicate `{token}`.
+    UnknownPredicate { line: usize, token: String },
+
+    /// line {line} contains invalid attribute `{token}`.
+    InvalidAttr { line: usize, token: String },
+
+    /// attribute block started at line {0} is not terminated.
+    UnterminatedBlock(usize),
+
+    /// expression at line {0} has too many attributes or nested expressions.
+    TooMany(usize),
+}
+
+impl FromStr for Pred {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "is" => Pred::Is,
+            "tuple" => Pred::Tuple,
+            "rec" => Pred::Rec,
+            "enum" => Pred::Enum,
+            "union" => Pred::Union,
+            "flags" => Pred::Flags,
+            "char" => Pred::Char,
+            "str" => Pred::Str,
+            "ascii" => Pred::Ascii,
+            "bytes" => Pred::Bytes,
+            "array" => Pred::Array,
+            "list" => Pred::List,
+            "set" => Pred::Set,
+            "map" => Pred::Map,
+            _ => match s.strip_prefix("decimal:").map(u8::from_str) {
+                Some(Ok(scale)) => Pred::Decimal { scale },
+                _ => return Err(s.to_owned()),
+            },
+        })
+    }
+}
+
+/// Parses textual Vesper representation of type layouts back into a list of top-level Vesper
+/// expressions.
+///
+/// Only the expression tree is reconstructed; the parser doesn't resolve any types and doesn't
+/// compute semantic type ids.
+pub fn parse_vesper(input: &str) -> Result<Vec<TypeVesper>, VesperParseError> {
+    let mut roots = vec![];
+    let mut stack: Vec<(usize, TypeVesper)> = vec![];
+
+    let mut lines = input.lines().enumerate().map(|(no, line)| (no + 1, line));
+    while let Some((no, line)) = lines.next() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let content = line.trim_start_matches(' ');
+        let spaces = line.len() - content.len();
+        if spaces % 2 != 0 {
+            return Err(VesperParseError::Indent(no));
+        }
+        let depth = spaces / 2;
+
+        let (main, comment) = match content.strip_suffix(" {") {
+            Some(head) => {
+                let mut main = head.to_owned();
+                let comment = loop {
+                    let Some((_, line)) = lines.next() else {
+                        return Err(VesperParseError::UnterminatedBlock(no));
+                    };
+                    let line = line.trim();
+                    if let Some(rest) = line.strip_prefix('}') {
+                        break rest.strip_prefix(" -- ").map(str::to_owned);
+                    }
+                    main.push(' ');
+                    main.push_str(line);
+                };
+                (main, comment)
+            }
+            None => match content.split_once(" -- ") {
+                Some((main, comment)) => (main.to_owned(), Some(comment.to_owned())),
+                None => (content.to_owned(), None),
+            },
+        };
+
+        let mut tokens = tokenize(&main).into_iter();
+        let subject = tokens.next().ok_or(VesperParseError::NoPredicate(no))?;
+        let subject = parse_ident(no, subject)?;
+        let predicate = tokens.next().ok_or(VesperParseError::NoPredicate(no))?;
+        let predicate = Pred::from_str(predicate)
+            .map_err(|token| VesperParseError::UnknownPredicate { line: no, token })?;
+
+        if depth > stack.len() {
+            return Err(VesperParseError::Indent(no));
+        }
+        while stack.len() > depth {
+            close(&mut stack, &mut roots)?;
+        }
+
+        // Enum variants are rendered as `name=tag`, so on enum lines they take precedence over
+        // attributes with the same name. The only other numeric attribute there is the tag of a
+        // union variant, which is rendered after all enum variants.
+        let tokens = tokens.collect::<Vec<_>>();
+        let variants = matches!(predicate, Pred::Enum | Pred::Flags | Pred::Char);
+        let union_tag = match stack.last() {
+            Some((_, parent)) if parent.predicate == Pred::Union => {
+                tokens.iter().rposition(|token| token.starts_with("tag="))
+            }
+            _ => None,
+        };
+        let attributes = tokens
+            .into_iter()
+            .enumerate()
+            .map(|(pos, token)| parse_attr(no, token, variants && union_tag != Some(pos)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let expr = TypeVesper {
+            subject,
+            predicate,
+            attributes: Confined::try_from(attributes)
+                .map_err(|_| VesperParseError::TooMany(no))?,
+            content: none!(),
+            comment,
+        };
+        stack.push((no, expr));
+    }
+    while !stack.is_empty() {
+        close(&mut stack, &mut roots)?;
+    }
+    Ok(roots)
+}
+
+fn close(
+    stack: &mut Vec<(usize, TypeVesper)>,
+    roots: &mut Vec<TypeVesper>,
+) -> Result<(), VesperParseError> {
+    let (no, expr) = stack.pop().expect("closing empty expression stack");
+    match stack.last_mut() {
+        Some((_, parent)) => {
+            parent.content.push(Box::new(expr)).map_err(|_| VesperParseError::TooMany(no))
+        }
+        None => {
+            roots.push(expr);
+            Ok(())
+        }
+    }
+}
+
+/// Splits line into whitespace-separated tokens, keeping bracketed values intact.
+///
+/// Inside brackets a backslash escapes the following character.
+fn tokenize(s: &str) -> Vec<&str> {
+    let mut tokens = vec![];
+    let mut start = None;
+    let mut brackets = 0usize;
+    let mut escaped = false;
+    for (pos, c) in s.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' if brackets > 0 => escaped = true,
+            '[' => brackets += 1,
+            ']' => brackets = brackets.saturating_sub(1),
+            c if c.is_whitespace() && brackets == 0 => {
+                if let Some(start) = start.take() {
+                    tokens.push(&s[start..pos]);
+                }
+                continue;
+            }
+            _ => {}
+        }
+        start.get_or_insert(pos);
+    }
+    if let Some(start) = start {
+        tokens.push(&s[start..]);
+    }
+    tokens
+}
+
+fn parse_ident(line: usize, token: &str) -> Result<Ident, VesperParseError> {
+    Ident::from_str(token).map_err(|_| VesperParseError::InvalidIdent {
+        line,
+        token: token.to_owned(),
+    })
+}
+
+/// Extracts a value rendered inside `[..]`, resolving backslash escapes. Unescaped brackets
+/// inside the value are rejected.
+fn parse_bracketed(value: &str) -> Option<String> {
+    let value = value.strip_prefix('[')?.strip_suffix(']')?;
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => unescaped.push(chars.next()?),
+            '[' | ']' => return None,
+            c => unescaped.push(c),
+        }
+    }
+    Some(unescaped)
+}
+
+/// Parses a single attribute token. With `variants` set, `name=tag` tokens are always read as
+/// enum variants.
+fn parse_attr(line: usize, token: &str, variants: bool) -> Result<Attr, VesperParseError> {
+    let err = || VesperParseError::InvalidAttr {
+        line,
+        token: token.to_owned(),
+    };
+    let Some((name, value)) = token.split_once('=') else {
+        return Ok(match token {
+            "option" => Attr::Option,
+            "wrapped" => Attr::Wrapped(None),
+            _ => Attr::TypeName(parse_ident(line, token)?),
+        });
+    };
+    if let Some(tag) = value.parse().ok().filter(|_| variants) {
+        return Ok(Attr::EnumVariant(tag, parse_ident(line, name)?));
+    }
+    Ok(match name {
+        "aka" => Attr::Wrapped(Some(parse_ident(line, value)?)),
+        "tag" => Attr::Tag(value.parse().map_err(|_| err())?),
+        "len" => match value.split_once("..") {
+            Some((min, max)) => {
+                let min = min.parse().map_err(|_| err())?;
+                let max = match max {
+                    "MAX8" => u8::MAX as u64,
+                    "MAX16" => u16::MAX as u64,
+                    "MAX24" => u24::MAX.into_u64(),
+                    "MAX32" => u32::MAX as u64,
+                    "MAX64" => u64::MAX,
+                    max => max.parse().map_err(|_| err())?,
+                };
+                Attr::LenRange(LenRange::from(Sizing::new(min, max)))
+            }
+            None => Attr::Len(value.parse().map_err(|_| err())?),
+        },
+        "charset" => Attr::AsciiEnum(parse_ident(line, value)?),
+        "first" => Attr::AsciiFirst(parse_ident(line, value)?),
+        "rest" => Attr::AsciiRest(parse_ident(line, value)?),
+        "range" => {
+            let (min, max) = value.split_once("..").ok_or_else(err)?;
+            Attr::Range(min.parse().map_err(|_| err())?, max.parse().map_err(|_| err())?)
+        }
+        "key" => Attr::KeyType(parse_bracketed(value).ok_or_else(err)?),
+        "default" => Attr::Default(parse_bracketed(value).ok_or_else(err)?),
+        name => Attr::EnumVariant(value.parse().map_err(|_| err())?, parse_ident(line, name)?),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use strict_encoding::{libname, STRICT_TYPES_LIB};
+
+    use super::*;
+    use crate::stl::std_stl;
+    use crate::typesys::SymTy;
+    use crate::{LibBuilder, SemId, SymbolicSys, SystemBuilder, Ty, TypeLib};
+
+    #[test]
+    fn round_trip() {
+        let std = std_stl();
+        let lib = LibBuilder::new(libname!(STRICT_TYPES_LIB), [std.to_dependency()])
+            .transpile::<TypeLib>()
+            .compile()
+            .unwrap();
+        let sys =
+            SystemBuilder::new().import(lib).unwrap().import(std).unwrap().finalize().unwrap();
+        let vesper = sys.type_tree("StrictTypes.TypeLib").unwrap().to_layout().to_vesper();
+
+        let text = vesper.display().to_string();
+        assert_eq!(parse_vesper(&text), Ok(vec![vesper]));
+    }
+
+    #[test]
+    fn variants_named_as_attrs() {
+        let kind = Ty::<SemId>::Enum(variants!("len", "default", "range", "tag"));
+        let kind_id = kind.sem_id_unnamed();
+        let choice = Ty::<SemId>::Union(variants!("none" => SemId::unit(), "kind" => kind_id));
+        let choice_id = choice.sem_id_unnamed();
+        let types = [(SemId::unit(), Ty::UNIT), (kind_id, kind), (choice_id, choice)]
+            .into_iter()
+            .map(|(id, ty)| (id, SymTy::unnamed(ty)))
+            .collect();
+        let sys = SymbolicSys::with(None, types).unwrap();
+        let vesper = sys.type_tree(choice_id).unwrap().to_layout().to_vesper();
+
+        let text = vesper.display().to_string();
+        assert!(text.contains("kind enum len=0 default=1 range=2 tag=3 tag=1"));
+        assert_eq!(parse_vesper(&text), Ok(vec![vesper]));
+    }
+
+    #[test]
+    fn escaped_brackets() {
+        let mut map = TypeVesper {
+            subject: ident!("Map"),
+            predicate: Pred::Map,
+            attributes: Confined::from_checked(vec![
+                Attr::KeyType(s!("a]b [c")),
+                Attr::Default(s!("[1, 2]] \\")),
+            ]),
+            content: none!(),
+            comment: None,
+        };
+        map.content
+            .push(Box::new(TypeVesper {
+                subject: ident!("value"),
+                predicate: Pred::Is,
+                attributes: Confined::from_checked(vec![Attr::TypeName(ident!("U8"))]),
+                content: none!(),
+                comment: None,
+            }))
+            .unwrap();
+
+        let text = map.display().to_string();
+        assert_eq!(parse_vesper(&text), Ok(vec![map]));
+    }
+
+    #[test]
+    fn unescaped_bracket() {
+        assert_eq!(
+            parse_vesper("Map map default=[a]b]\n"),
+            Err(VesperParseError::InvalidAttr {
+                line: 1,
+                token: s!("default=[a]b]")
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_indent() {
+        assert_eq!(parse_vesper("root rec\n   field is U8\n"), Err(VesperParseError::Indent(2)));
+    }
+}
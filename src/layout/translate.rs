@@ -21,15 +21,14 @@
 // limitations under the License.
 
 use amplify::confinement::Confined;
-use encoding::Primitive;
 
-use super::vesper::{Attr, Pred, TypeVesper};
+use super::vesper::{Annotations, Attr, Pred, TypeVesper};
 use crate::ast::ItemCase;
 use crate::typesys::{NestedCase, TypeInfo};
 use crate::Ty;
 
 impl TypeInfo {
-    pub(super) fn to_vesper(&self) -> TypeVesper {
+    pub(super) fn to_vesper(&self, annotations: &Annotations) -> TypeVesper {
         let TypeInfo {
             ty,
             fqn,
@@ -40,6 +39,7 @@ impl TypeInfo {
 
         let mut attributes = vec![];
         let mut comment = None;
+        let default = fqn.as_ref().and_then(|fqn| annotations.defaults.get(fqn)).cloned();
         let name = fqn.as_ref().map(|f| f.name.clone()).unwrap_or_else(|| tn!("_"));
         let fqn = fqn.as_ref().map(|f| f.name.to_string());
         let subject = match item {
@@ -84,11 +84,9 @@ impl TypeInfo {
         };
         let mut predicate = ty.cls().into();
         match ty {
-            Ty::Primitive(prim) if *prim == Primitive::UNIT => {
-                attributes.push(Attr::TypeName(ident!("Unit")));
-            }
-            Ty::Primitive(prim) => {
-                attributes.push(Attr::TypeName(ident!("{}", prim)));
+            Ty::Primitive(_) => {
+                let name = ty.primitive_name().expect("primitive type");
+                attributes.push(Attr::TypeName(ident!("{}", name)));
             }
             Ty::Array(_, len) => attributes.push(Attr::Len(*len)),
             Ty::List(_, sizing) | Ty::Set(_, sizing) | Ty::Map(_, _, sizing) => {
@@ -96,6 +94,11 @@ impl TypeInfo {
             }
             _ => {}
         }
+        if ty.is_char_enum() || ty.is_numeric_enum() {
+            if let Some((min, max)) = ty.contiguous_tags() {
+                attributes.push(Attr::Range(min, max));
+            }
+        }
         if ty.is_char_enum() {
             predicate = Pred::Char;
         } else if ty.is_byte_array() {
@@ -126,11 +129,18 @@ impl TypeInfo {
                     predicate = Pred::Str;
                 }
                 NestedCase::NewType(fqn) => {
+                    let scale = fqn.as_ref().and_then(|fqn| annotations.decimals.get(fqn));
+                    if let Some(scale) = scale.filter(|_| ty.numeric_bounds().is_some()) {
+                        predicate = Pred::Decimal { scale: *scale };
+                    }
                     attributes.push(Attr::Wrapped(fqn.as_ref().map(|f| f.name.to_ident())));
                 }
                 NestedCase::Option => {
                     attributes.push(Attr::Option);
                 }
+                NestedCase::BitFlags => {
+                    predicate = Pred::Flags;
+                }
             }
         }
 
@@ -142,6 +152,9 @@ impl TypeInfo {
         if let Some(ItemCase::UnionVariant(ref pos, _)) = item {
             attributes.push(Attr::Tag(*pos));
         }
+        if let Some(default) = default {
+            attributes.push(Attr::Default(default));
+        }
 
         TypeVesper {
             subject,
@@ -152,3 +165,112 @@ impl TypeInfo {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeSet;
+
+    use encoding::Variant;
+
+    use super::*;
+    use crate::ast::EnumVariants;
+    use crate::typesys::{SymTy, TypeFqn};
+    use crate::{SemId, SymbolicSys};
+
+    #[test]
+    fn char_enum_range() {
+        let info = TypeInfo {
+            depth: 0,
+            ty: Ty::Enum(variants!(32..=126)),
+            fqn: None,
+            item: None,
+            nested: none!(),
+        };
+        let vesper = info.to_vesper(&none!());
+        assert_eq!(vesper.predicate, Pred::Char);
+        assert!(vesper.attributes.contains(&Attr::Range(32, 126)));
+        assert!(vesper.display().to_string().contains("range=32..126"));
+    }
+
+    #[test]
+    fn char_enum_gaps() {
+        let alpha_num = (b'0'..=b'9')
+            .chain(b'A'..=b'Z')
+            .map(|tag| Variant::named(tag, vname!(format!("_{tag}"))))
+            .collect::<BTreeSet<_>>();
+        let info = TypeInfo {
+            depth: 0,
+            ty: Ty::Enum(EnumVariants::try_from(alpha_num).unwrap()),
+            fqn: None,
+            item: None,
+            nested: none!(),
+        };
+        let vesper = info.to_vesper(&none!());
+        assert_eq!(vesper.predicate, Pred::Char);
+        assert!(!vesper.attributes.iter().any(|attr| matches!(attr, Attr::Range(..))));
+    }
+
+    #[test]
+    fn numeric_enum_range() {
+        let domain = vesper([Ty::Enum(variants!(0..=15))]);
+        assert_eq!(domain.predicate, Pred::Enum);
+        assert!(domain.attributes.contains(&Attr::Range(0, 15)));
+
+        let plain = vesper([Ty::Enum(variants!("a", "b"))]);
+        assert!(!plain.attributes.iter().any(|attr| matches!(attr, Attr::Range(..))));
+    }
+
+    /// Builds layout of the last of the `types` through the type tree.
+    fn vesper(types: impl IntoIterator<Item = Ty<SemId>>) -> TypeVesper {
+        let types = types.into_iter().map(|ty| (ty.sem_id_unnamed(), ty)).collect::<Vec<_>>();
+        let (id, _) = *types.last().expect("no types");
+        let types = types.into_iter().map(|(id, ty)| (id, SymTy::unnamed(ty))).collect();
+        let sys = SymbolicSys::with(None, types).unwrap();
+        sys.type_tree(id).unwrap().to_layout().to_vesper()
+    }
+
+    #[test]
+    fn bit_flags() {
+        let flags = EnumVariants::try_from(bset! {
+            Variant::named(1, vname!("read")),
+            Variant::named(2, vname!("write")),
+            Variant::named(4, vname!("exec")),
+        })
+        .unwrap();
+        assert_eq!(vesper([Ty::Enum(flags)]).predicate, Pred::Flags);
+        assert_eq!(vesper([Ty::Enum(variants!("a", "b"))]).predicate, Pred::Enum);
+    }
+
+    #[test]
+    fn char_arrays() {
+        let unicode = Ty::<SemId>::Array(Ty::<SemId>::UNICODE.sem_id_unnamed(), 4);
+        assert_eq!(vesper([Ty::UNICODE, unicode]).predicate, Pred::Str);
+
+        let char = Ty::<SemId>::Enum(variants!(32..=126));
+        let ascii = Ty::<SemId>::Array(char.sem_id_unnamed(), 4);
+        let vesper = vesper([char, ascii]);
+        assert_eq!(vesper.predicate, Pred::Ascii);
+        assert!(vesper.content.is_empty());
+    }
+
+    #[test]
+    fn decimal() {
+        let fqn = TypeFqn::with(libname!("Test"), tn!("Amount"));
+        let info = TypeInfo {
+            depth: 0,
+            ty: Ty::U64,
+            fqn: None,
+            item: None,
+            nested: tiny_vec![NestedCase::NewType(Some(fqn.clone()))],
+        };
+        assert_eq!(info.to_vesper(&none!()).predicate, Pred::Is);
+
+        let annotations = Annotations {
+            decimals: bmap! { fqn => 8 },
+            ..none!()
+        };
+        let vesper = info.to_vesper(&annotations);
+        assert_eq!(vesper.predicate, Pred::Decimal { scale: 8 });
+        assert!(vesper.display().to_string().contains("decimal:8 U64"));
+    }
+}
@@ -20,6 +20,7 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use std::collections::BTreeMap;
 use std::fmt;
 use std::fmt::{Display, Formatter};
 use std::ops::Range;
@@ -28,6 +29,7 @@ use amplify::num::u24;
 use encoding::{Ident, Sizing};
 use vesper::{AttrVal, Attribute, Expression, Predicate, TExpr};
 
+use crate::typesys::TypeFqn;
 use crate::Cls;
 
 pub type TypeVesper = TExpr<Pred>;
@@ -41,6 +43,7 @@ pub enum Pred {
     Rec,
     Enum,
     Union,
+    Flags,
     // Composites
     Char,
     Str,
@@ -51,6 +54,11 @@ pub enum Pred {
     List,
     Set,
     Map,
+    /// Integer holding a fixed-point number, scaled by `10^-scale`.
+    #[display("decimal:{scale}")]
+    Decimal {
+        scale: u8,
+    },
 }
 
 impl Predicate for Pred {
@@ -82,6 +90,12 @@ pub enum AttrExpr {
     EnumVariant(u8),
     Len(u16),
     LenRange(LenRange),
+    #[display("{0}..{1}")]
+    Range(u8, u8),
+    #[display("[{0}]")]
+    KeyType(String),
+    #[display("[{0}]")]
+    Default(String),
 }
 
 impl Expression for AttrExpr {}
@@ -98,6 +112,12 @@ pub enum Attr {
     EnumVariant(u8, Ident),
     Len(u16),
     LenRange(LenRange),
+    /// Inclusive range of values allowed by the type.
+    Range(u8, u8),
+    /// Short description of a map key type.
+    KeyType(String),
+    /// Conventional default value of the type, as provided by [`Annotations`].
+    Default(String),
 }
 
 impl Attribute for Attr {
@@ -112,6 +132,9 @@ impl Attribute for Attr {
             Attr::Tag(_) => Some(ident!("tag")),
             Attr::Len(_) => Some(ident!("len")),
             Attr::LenRange(_) => Some(ident!("len")),
+            Attr::Range(_, _) => Some(ident!("range")),
+            Attr::KeyType(_) => Some(ident!("key")),
+            Attr::Default(_) => Some(ident!("default")),
             Attr::AsciiEnum(_) => Some(ident!("charset")),
             Attr::AsciiFirst(_) => Some(ident!("first")),
             Attr::AsciiRest(_) => Some(ident!("rest")),
@@ -127,6 +150,9 @@ impl Attribute for Attr {
             Attr::Tag(tag) => AttrVal::Expr(AttrExpr::Tag(*tag)),
             Attr::Len(len) => AttrVal::Expr(AttrExpr::Len(*len)),
             Attr::LenRange(range) => AttrVal::Expr(AttrExpr::LenRange(range.clone())),
+            Attr::Range(min, max) => AttrVal::Expr(AttrExpr::Range(*min, *max)),
+            Attr::KeyType(desc) => AttrVal::Expr(AttrExpr::KeyType(escape_bracketed(desc))),
+            Attr::Default(val) => AttrVal::Expr(AttrExpr::Default(escape_bracketed(val))),
             Attr::AsciiEnum(name) => AttrVal::Ident(name.clone()),
             Attr::AsciiFirst(name) => AttrVal::Ident(name.clone()),
             Attr::AsciiRest(name) => AttrVal::Ident(name.clone()),
@@ -135,9 +161,32 @@ impl Attribute for Attr {
     }
 }
 
+/// Escapes backslashes and square brackets in a value rendered inside `[..]`, so the Vesper
+/// parser can find the closing bracket.
+fn escape_bracketed(val: &str) -> String {
+    let mut escaped = String::with_capacity(val.len());
+    for c in val.chars() {
+        if matches!(c, '\\' | '[' | ']') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
 #[derive(Clone, Eq, PartialEq, Hash, Debug)]
 pub struct LenRange(Range<u64>);
 
+/// Information about types which is not a part of the type system, but may be
+/// added to the generated Vesper.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct Annotations {
+    /// Default values of named types, in their textual representation.
+    pub defaults: BTreeMap<TypeFqn, String>,
+    /// Decimal scales of named newtypes wrapping integers, which represent fixed-point numbers.
+    pub decimals: BTreeMap<TypeFqn, u8>,
+}
+
 impl From<Sizing> for LenRange {
     #[inline]
     fn from(sizing: Sizing) -> Self { Self(sizing.min..sizing.max) }
@@ -156,3 +205,138 @@ impl Display for LenRange {
         }
     }
 }
+
+/// Style of textual Vesper rendering performed by [`VesperRender::render_with`].
+#[derive(Copy, Clone, Eq, PartialEq, Hash, Debug)]
+pub struct VesperStyle {
+    /// Number of spaces used for each indentation level.
+    pub indent: usize,
+    /// Maximal line width; expressions with attributes not fitting it have their attributes
+    /// wrapped into a block spanning multiple lines.
+    pub max_width: usize,
+}
+
+impl Default for VesperStyle {
+    fn default() -> Self {
+        VesperStyle {
+            indent: 2,
+            max_width: 100,
+        }
+    }
+}
+
+/// Rendering of Vesper expressions with a configurable [`VesperStyle`].
+///
+/// Unlike [`TypeVesper::display`], which is the canonical form understood by the Vesper parser,
+/// the output of non-default styles is intended for documentation only.
+pub trait VesperRender {
+    fn render_with(&self, style: &VesperStyle) -> String;
+}
+
+impl VesperRender for TypeVesper {
+    fn render_with(&self, style: &VesperStyle) -> String {
+        let mut output = String::new();
+        render_expr(self, style, 0, &mut output);
+        output
+    }
+}
+
+fn render_expr(expr: &TypeVesper, style: &VesperStyle, depth: usize, output: &mut String) {
+    let indent = " ".repeat(style.indent * depth);
+    let inner = " ".repeat(style.indent * (depth + 1));
+    let attrs = expr
+        .attributes
+        .iter()
+        .map(|attr| match attr.name() {
+            Some(name) => format!("{name}={}", attr.value()),
+            None => attr.value().to_string(),
+        })
+        .collect::<Vec<_>>();
+
+    let head = format!("{indent}{} {}", expr.subject, expr.predicate);
+    let line = attrs.iter().fold(head.clone(), |line, attr| format!("{line} {attr}"));
+    if line.len() <= style.max_width {
+        output.push_str(&line);
+    } else {
+        output.push_str(&head);
+        output.push_str(" {");
+        let mut line = inner.clone();
+        for attr in attrs {
+            if line.len() > inner.len() && line.len() + 1 + attr.len() > style.max_width {
+                output.push('\n');
+                output.push_str(&line);
+                line = inner.clone();
+            }
+            if line.len() > inner.len() {
+                line.push(' ');
+            }
+            line.push_str(&attr);
+        }
+        output.push('\n');
+        output.push_str(&line);
+        output.push('\n');
+        output.push_str(&indent);
+        output.push('}');
+    }
+    if let Some(comment) = &expr.comment {
+        output.push_str(" -- ");
+        output.push_str(comment);
+    }
+    output.push('\n');
+    for nested in &expr.content {
+        render_expr(nested, style, depth + 1, output);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::str::FromStr;
+
+    use amplify::confinement::Confined;
+
+    use super::*;
+
+    fn expr(subject: &str, predicate: Pred, attributes: Vec<Attr>) -> TypeVesper {
+        TypeVesper {
+            subject: Ident::from_str(subject).unwrap(),
+            predicate,
+            attributes: Confined::from_checked(attributes),
+            content: none!(),
+            comment: None,
+        }
+    }
+
+    #[test]
+    fn render_with() {
+        let mut rec = expr("Rec", Pred::Rec, vec![]);
+        let mut items = expr("items", Pred::List, vec![Attr::LenRange(Sizing::U8.into())]);
+        items
+            .content
+            .push(Box::new(expr("element", Pred::Is, vec![Attr::TypeName(ident!("U16"))])))
+            .unwrap();
+        rec.content.push(Box::new(items)).unwrap();
+
+        let default = rec.render_with(&VesperStyle::default());
+        assert_eq!(default, rec.display().to_string());
+        assert_eq!(default, "Rec rec\n  items list len=0..MAX8\n    element is U16\n");
+        let wide = rec.render_with(&VesperStyle {
+            indent: 4,
+            max_width: 100,
+        });
+        assert_eq!(wide, "Rec rec\n    items list len=0..MAX8\n        element is U16\n");
+    }
+
+    #[test]
+    fn render_wrapped() {
+        let attrs = (0..4u8).map(|tag| Attr::EnumVariant(tag, ident!("variant{}", tag))).collect();
+        let enm = expr("Kind", Pred::Enum, attrs);
+        let style = VesperStyle {
+            indent: 2,
+            max_width: 30,
+        };
+        assert_eq!(
+            enm.render_with(&style),
+            "Kind enum {\n  variant0=0 variant1=1\n  variant2=2 variant3=3\n}\n"
+        );
+    }
+}
@@ -0,0 +1,131 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Linking of multiple type libraries into a single type system.
+
+use std::collections::BTreeSet;
+use std::fmt::{self, Display, Formatter};
+
+use crate::typesys::{self, SystemBuilder};
+use crate::{SemId, TypeLib, TypeSystem};
+
+#[derive(Clone, Eq, PartialEq, Debug, Error)]
+pub enum LinkError {
+    /// Some of the types referenced by the libraries are not defined in any of them.
+    Unresolved(BTreeSet<SemId>),
+
+    /// Type system can't be built from the provided libraries.
+    Build(Vec<typesys::Error>),
+}
+
+impl Display for LinkError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            LinkError::Unresolved(ids) => {
+                f.write_str("unable to link type libraries due to unresolved type references:")?;
+                for id in ids {
+                    write!(f, "\n- {id}")?;
+                }
+                Ok(())
+            }
+            LinkError::Build(errors) => {
+                f.write_str("unable to link type libraries:")?;
+                for err in errors {
+                    write!(f, "\n- {err}")?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Merges type libraries into a single type system, resolving external references of each of
+/// the libraries against the types defined by all provided libraries.
+///
+/// Fails with the list of dangling type ids if some of the external references can't be
+/// resolved.
+pub fn link(libs: Vec<TypeLib>) -> Result<TypeSystem, LinkError> {
+    let defined = libs
+        .iter()
+        .flat_map(|lib| lib.types.iter().map(|(name, ty)| ty.sem_id_named(name)))
+        .collect::<BTreeSet<_>>();
+    let unresolved = libs
+        .iter()
+        .flat_map(TypeLib::external_refs)
+        .filter(|id| !defined.contains(id))
+        .collect::<BTreeSet<_>>();
+    if !unresolved.is_empty() {
+        return Err(LinkError::Unresolved(unresolved));
+    }
+
+    let mut builder = SystemBuilder::new();
+    for lib in libs {
+        builder = builder.import(lib).map_err(|err| LinkError::Build(vec![err]))?;
+    }
+    let sys = builder.finalize().map_err(LinkError::Build)?;
+    Ok(sys.into_type_system())
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::Confined;
+
+    use super::*;
+    use crate::typelib::{ExternRef, LibRef};
+    use crate::Ty;
+
+    fn libs() -> (TypeLib, TypeLib, SemId) {
+        let inner = Ty::<LibRef>::U8;
+        let inner_id = inner.sem_id_named(&tn!("Inner"));
+        let base = TypeLib {
+            name: libname!("Base"),
+            dependencies: none!(),
+            extern_types: none!(),
+            types: Confined::from_checked(bmap! { tn!("Inner") => inner }),
+        };
+        let outer = Ty::<LibRef>::Struct(fields!(
+            "inner" => LibRef::Extern(ExternRef::with(base.id(), inner_id)),
+        ));
+        let app = TypeLib {
+            name: libname!("App"),
+            dependencies: Confined::from_checked(bset! { base.to_dependency() }),
+            extern_types: none!(),
+            types: Confined::from_checked(bmap! { tn!("Outer") => outer }),
+        };
+        (base, app, inner_id)
+    }
+
+    #[test]
+    fn link_imported() {
+        let (base, app, inner_id) = libs();
+        let outer_id = app.types.iter().map(|(name, ty)| ty.sem_id_named(name)).next().unwrap();
+        let sys = link(vec![app, base]).unwrap();
+        assert!(sys.get(inner_id).is_some());
+        assert!(sys.get(outer_id).is_some());
+    }
+
+    #[test]
+    fn link_missing() {
+        let (_, app, inner_id) = libs();
+        assert_eq!(link(vec![app]), Err(LinkError::Unresolved(bset![inner_id])));
+    }
+}
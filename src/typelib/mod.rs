@@ -27,12 +27,14 @@ mod serialize;
 mod transpile;
 mod symbolic;
 mod translate;
+mod link;
 
 pub(crate) use compile::NestedContext;
 #[allow(deprecated)]
 pub use compile::TranslateError;
 pub use compile::{CompileError, TypeIndex};
 pub use id::TypeLibId;
+pub use link::{link, LinkError};
 pub use symbolic::{ExternTypes, SymbolRef, SymbolicLib, TranspileError, TranspileRef};
 use translate::SymbolContext;
 pub use translate::SymbolError;
@@ -21,6 +21,7 @@
 // limitations under the License.
 
 use std::cmp::Ordering;
+use std::collections::BTreeSet;
 use std::fmt::{self, Display, Formatter};
 
 use amplify::confinement::{NonEmptyOrdMap, TinyOrdSet};
@@ -306,5 +307,136 @@ impl TypeLib {
         Ok(())
     }
 
+    /// Iterates over names of all types defined by the library.
+    pub fn type_names(&self) -> impl Iterator<Item = &TypeName> { self.types.keys() }
+
+    /// Returns type defined by the library under the given name.
+    pub fn get(&self, name: &TypeName) -> Option<&Ty<LibRef>> { self.types.get(name) }
+
+    /// Returns semantic ids of all types referenced by the library types (directly or from
+    /// inline types) which are not defined by the library itself.
+    pub fn external_refs(&self) -> BTreeSet<SemId> {
+        let defined =
+            self.types.iter().map(|(name, ty)| ty.sem_id_named(name)).collect::<BTreeSet<_>>();
+        let mut refs = BTreeSet::new();
+        for ty in self.types.values() {
+            ty.collect_refs(&mut refs);
+        }
+        refs.retain(|id| !defined.contains(id));
+        refs
+    }
+
     // TODO: Check that all dependencies are used
 }
+
+trait CollectRefs {
+    fn collect_refs(&self, refs: &mut BTreeSet<SemId>);
+}
+
+impl<Ref: TypeRef + CollectRefs> CollectRefs for Ty<Ref> {
+    fn collect_refs(&self, refs: &mut BTreeSet<SemId>) {
+        for (r, _) in self {
+            r.collect_refs(refs);
+        }
+    }
+}
+
+impl CollectRefs for LibRef {
+    fn collect_refs(&self, refs: &mut BTreeSet<SemId>) {
+        match self {
+            LibRef::Inline(ty) => ty.collect_refs(refs),
+            LibRef::Named(id) => {
+                refs.insert(*id);
+            }
+            LibRef::Extern(ext) => {
+                refs.insert(ext.sem_id);
+            }
+        }
+    }
+}
+
+impl CollectRefs for InlineRef {
+    fn collect_refs(&self, refs: &mut BTreeSet<SemId>) {
+        match self {
+            InlineRef::Inline(ty) => ty.collect_refs(refs),
+            InlineRef::Named(id) => {
+                refs.insert(*id);
+            }
+            InlineRef::Extern(ext) => {
+                refs.insert(ext.sem_id);
+            }
+        }
+    }
+}
+
+impl CollectRefs for InlineRef1 {
+    fn collect_refs(&self, refs: &mut BTreeSet<SemId>) {
+        match self {
+            InlineRef1::Inline(ty) => ty.collect_refs(refs),
+            InlineRef1::Named(id) => {
+                refs.insert(*id);
+            }
+            InlineRef1::Extern(ext) => {
+                refs.insert(ext.sem_id);
+            }
+        }
+    }
+}
+
+impl CollectRefs for InlineRef2 {
+    fn collect_refs(&self, refs: &mut BTreeSet<SemId>) {
+        match self {
+            InlineRef2::Named(id) => {
+                refs.insert(*id);
+            }
+            InlineRef2::Extern(ext) => {
+                refs.insert(ext.sem_id);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::Confined;
+    use encoding::Sizing;
+
+    use super::*;
+
+    #[test]
+    fn external_refs() {
+        let inner = Ty::<LibRef>::U8;
+        let inner_id = inner.sem_id_named(&tn!("Inner"));
+        let missing = Ty::<LibRef>::U16.sem_id_named(&tn!("Missing"));
+        let outer = Ty::<LibRef>::Struct(fields!(
+            "inner" => LibRef::Named(inner_id),
+            "missing" => LibRef::Inline(Ty::List(InlineRef::Named(missing), Sizing::U8)),
+        ));
+        let lib = TypeLib {
+            name: libname!("Test"),
+            dependencies: none!(),
+            extern_types: none!(),
+            types: Confined::from_checked(bmap! {
+                tn!("Inner") => inner,
+                tn!("Outer") => outer,
+            }),
+        };
+        assert_eq!(lib.external_refs(), bset![missing]);
+    }
+
+    #[test]
+    fn type_names() {
+        let lib = TypeLib {
+            name: libname!("Test"),
+            dependencies: none!(),
+            extern_types: none!(),
+            types: Confined::from_checked(bmap! {
+                tn!("First") => Ty::<LibRef>::U8,
+                tn!("Second") => Ty::<LibRef>::U16,
+            }),
+        };
+        assert_eq!(lib.type_names().collect::<Vec<_>>(), vec![&tn!("First"), &tn!("Second")]);
+        assert_eq!(lib.get(&tn!("Second")), Some(&Ty::U16));
+        assert_eq!(lib.get(&tn!("Third")), None);
+    }
+}
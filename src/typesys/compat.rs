@@ -0,0 +1,401 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Checks of backward compatibility between versions of a type system, following strict encoding
+//! type evolution rules.
+
+use std::collections::BTreeSet;
+
+use encoding::{FieldName, Primitive, Sizing, VariantName};
+
+use crate::ast::SizingExt;
+use crate::typesys::{SymbolicSys, TypeFqn, TypeSymbol};
+use crate::{SemId, Ty};
+
+/// Checks whether `new` collection bounds are a superset of the `old` ones, i.e. any collection
+/// valid under the old bounds remains valid under the new bounds.
+pub fn sizing_is_wider(new: Sizing, old: Sizing) -> bool {
+    new.min <= old.min && new.max >= old.max
+}
+
+/// Change in a type system which prevents data encoded with an older schema from being decoded
+/// with a newer one.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum Incompatibility {
+    /// type `{0}` was removed.
+    TypeRemoved(TypeFqn),
+
+    /// type `{0}` is referenced in the type system but is absent from it.
+    TypeAbsent(SemId),
+
+    /// type `{ty}` has changed from {old} to {new}.
+    KindChanged {
+        ty: TypeSymbol,
+        old: &'static str,
+        new: &'static str,
+    },
+
+    /// primitive type `{ty}` has changed from {old} to {new}.
+    PrimitiveChanged {
+        ty: TypeSymbol,
+        old: Primitive,
+        new: Primitive,
+    },
+
+    /// field `{field}` of type `{ty}` was removed or reordered.
+    FieldRemoved { ty: TypeSymbol, field: FieldName },
+
+    /// field `{field}` was added to type `{ty}`.
+    FieldRequired { ty: TypeSymbol, field: FieldName },
+
+    /// number of fields in tuple `{ty}` has changed from {old} to {new}.
+    FieldCountChanged {
+        ty: TypeSymbol,
+        old: usize,
+        new: usize,
+    },
+
+    /// variant `{variant}` of type `{ty}` was removed.
+    VariantRemoved {
+        ty: TypeSymbol,
+        variant: VariantName,
+    },
+
+    /// variant `{variant}` of type `{ty}` has changed its tag from {old} to {new}.
+    TagChanged {
+        ty: TypeSymbol,
+        variant: VariantName,
+        old: u8,
+        new: u8,
+    },
+
+    /// array `{ty}` has changed its length from {old} to {new}.
+    LenChanged { ty: TypeSymbol, old: u16, new: u16 },
+
+    /// collection `{ty}` has narrowed its size limits from {old_min}..{old_max} to
+    /// {new_min}..{new_max}.
+    SizingNarrowed {
+        ty: TypeSymbol,
+        old_min: u64,
+        old_max: u64,
+        new_min: u64,
+        new_max: u64,
+    },
+
+    /// collection `{ty}` has changed its length prefix from {old} to {new} bytes.
+    PrefixChanged {
+        ty: TypeSymbol,
+        old: usize,
+        new: usize,
+    },
+}
+
+impl SymbolicSys {
+    /// Checks whether data encoded using `older` version of the type system can be decoded using
+    /// this type system.
+    ///
+    /// Types are matched by their fully qualified names. Appending new variants to unions and
+    /// enums is allowed; adding, removing or reordering fields (strict structures are positional,
+    /// so old data has no bytes for the appended fields, even for optional ones), changing
+    /// variant tags, primitive widths, narrowing collection size limits or changing the width of
+    /// collection length prefixes is reported as a breaking change.
+    pub fn is_compatible_with(&self, older: &SymbolicSys) -> Result<(), Vec<Incompatibility>> {
+        let mut checker = Checker {
+            older,
+            newer: self,
+            visited: empty!(),
+            issues: empty!(),
+        };
+        for sym in older.symbols.iter() {
+            let Some(fqn) = &sym.fqn else { continue };
+            match self.resolve(fqn.clone()) {
+                Some(new_id) => checker.check(sym.id, *new_id),
+                None => checker.issues.push(Incompatibility::TypeRemoved(fqn.clone())),
+            }
+        }
+        if checker.issues.is_empty() {
+            Ok(())
+        } else {
+            Err(checker.issues)
+        }
+    }
+}
+
+struct Checker<'sys> {
+    older: &'sys SymbolicSys,
+    newer: &'sys SymbolicSys,
+    visited: BTreeSet<(SemId, SemId)>,
+    issues: Vec<Incompatibility>,
+}
+
+impl Checker<'_> {
+    fn check(&mut self, old_id: SemId, new_id: SemId) {
+        if old_id == new_id || !self.visited.insert((old_id, new_id)) {
+            return;
+        }
+        let Some(old) = self.older.as_types().get(old_id) else {
+            self.issues.push(Incompatibility::TypeAbsent(old_id));
+            return;
+        };
+        let Some(new) = self.newer.as_types().get(new_id) else {
+            self.issues.push(Incompatibility::TypeAbsent(new_id));
+            return;
+        };
+        let ty = match self.older.lookup(old_id) {
+            Some(fqn) => TypeSymbol::with(old_id, fqn.clone()),
+            None => TypeSymbol::unnamed(old_id),
+        };
+
+        match (old, new) {
+            (Ty::Primitive(old), Ty::Primitive(new)) if old != new => {
+                self.issues.push(Incompatibility::PrimitiveChanged {
+                    ty,
+                    old: *old,
+                    new: *new,
+                });
+            }
+            (Ty::Primitive(_), Ty::Primitive(_)) | (Ty::UnicodeChar, Ty::UnicodeChar) => {}
+            (Ty::Enum(old), Ty::Enum(new)) => {
+                for variant in old {
+                    match new.iter().find(|v| v.name == variant.name) {
+                        None => self.issues.push(Incompatibility::VariantRemoved {
+                            ty: ty.clone(),
+                            variant: variant.name.clone(),
+                        }),
+                        Some(v) if v.tag != variant.tag => {
+                            self.issues.push(Incompatibility::TagChanged {
+                                ty: ty.clone(),
+                                variant: variant.name.clone(),
+                                old: variant.tag,
+                                new: v.tag,
+                            })
+                        }
+                        Some(_) => {}
+                    }
+                }
+            }
+            (Ty::Union(old), Ty::Union(new)) => {
+                for (variant, old_ty) in old {
+                    match new.iter().find(|(v, _)| v.name == variant.name) {
+                        None => self.issues.push(Incompatibility::VariantRemoved {
+                            ty: ty.clone(),
+                            variant: variant.name.clone(),
+                        }),
+                        Some((v, _)) if v.tag != variant.tag => {
+                            self.issues.push(Incompatibility::TagChanged {
+                                ty: ty.clone(),
+                                variant: variant.name.clone(),
+                                old: variant.tag,
+                                new: v.tag,
+                            })
+                        }
+                        Some((_, new_ty)) => self.check(*old_ty, *new_ty),
+                    }
+                }
+            }
+            (Ty::Struct(old), Ty::Struct(new)) => {
+                for (pos, field) in old.iter().enumerate() {
+                    match new.get(pos) {
+                        Some(f) if f.name == field.name => self.check(field.ty, f.ty),
+                        _ => self.issues.push(Incompatibility::FieldRemoved {
+                            ty: ty.clone(),
+                            field: field.name.clone(),
+                        }),
+                    }
+                }
+                for field in new.iter().skip(old.len()) {
+                    self.issues.push(Incompatibility::FieldRequired {
+                        ty: ty.clone(),
+                        field: field.name.clone(),
+                    });
+                }
+            }
+            (Ty::Tuple(old), Ty::Tuple(new)) if old.len() != new.len() => {
+                self.issues.push(Incompatibility::FieldCountChanged {
+                    ty,
+                    old: old.len(),
+                    new: new.len(),
+                });
+            }
+            (Ty::Tuple(old), Ty::Tuple(new)) => {
+                for (old_ty, new_ty) in old.iter().zip(new.iter()) {
+                    self.check(*old_ty, *new_ty);
+                }
+            }
+            (Ty::Array(_, old), Ty::Array(_, new)) if old != new => {
+                self.issues.push(Incompatibility::LenChanged {
+                    ty,
+                    old: *old,
+                    new: *new,
+                });
+            }
+            (Ty::Array(old_ty, _), Ty::Array(new_ty, _)) => self.check(*old_ty, *new_ty),
+            (Ty::List(old_ty, old), Ty::List(new_ty, new))
+            | (Ty::Set(old_ty, old), Ty::Set(new_ty, new)) => {
+                self.check_sizing(&ty, *old, *new);
+                self.check(*old_ty, *new_ty);
+            }
+            (Ty::Map(old_key, old_ty, old), Ty::Map(new_key, new_ty, new)) => {
+                self.check_sizing(&ty, *old, *new);
+                self.check(*old_key, *new_key);
+                self.check(*old_ty, *new_ty);
+            }
+            (old, new) => self.issues.push(Incompatibility::KindChanged {
+                ty,
+                old: old.kind_str(),
+                new: new.kind_str(),
+            }),
+        }
+    }
+
+    fn check_sizing(&mut self, ty: &TypeSymbol, old: Sizing, new: Sizing) {
+        // The width of the length prefix is defined by the maximal collection size, so widening
+        // the limits across the prefix boundary makes old data undecodable
+        if old.byte_size() != new.byte_size() {
+            self.issues.push(Incompatibility::PrefixChanged {
+                ty: ty.clone(),
+                old: old.byte_size(),
+                new: new.byte_size(),
+            });
+        } else if !sizing_is_wider(new, old) {
+            self.issues.push(Incompatibility::SizingNarrowed {
+                ty: ty.clone(),
+                old_min: old.min,
+                old_max: old.max,
+                new_min: new.min,
+                new_max: new.max,
+            });
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use encoding::{LibName, TypeName};
+
+    use super::*;
+    use crate::typelib::LibRef;
+    use crate::typesys::SymTy;
+    use crate::{SystemBuilder, Translate};
+
+    #[test]
+    fn sizing_wider() {
+        assert!(sizing_is_wider(Sizing::new(0, 100), Sizing::new(1, 10)));
+        assert!(sizing_is_wider(Sizing::new(1, 10), Sizing::new(1, 10)));
+        assert!(!sizing_is_wider(Sizing::new(2, 100), Sizing::new(1, 10)));
+        assert!(!sizing_is_wider(Sizing::new(0, 9), Sizing::new(1, 10)));
+    }
+
+    /// Builds a type system the way [`SystemBuilder`] imports libraries, with named types
+    /// identified by their names as well.
+    fn sys(types: impl IntoIterator<Item = (Option<&'static str>, Ty<LibRef>)>) -> SymbolicSys {
+        let map = types
+            .into_iter()
+            .map(|(name, ty)| {
+                let id = match name {
+                    Some(name) => ty.sem_id_named(&TypeName::from(name)),
+                    None => ty.sem_id_unnamed(),
+                };
+                let ty = ty.translate(&mut SystemBuilder::new(), &()).unwrap();
+                let sym = match name {
+                    Some(name) => SymTy::named(LibName::from("Test"), name.into(), ty),
+                    None => SymTy::unnamed(ty),
+                };
+                (id, sym)
+            })
+            .collect::<BTreeMap<_, _>>();
+        SymbolicSys::with(None, map).unwrap()
+    }
+
+    #[test]
+    fn trailing_field() {
+        let u32 = LibRef::Named(Ty::<SemId>::U32.sem_id_unnamed());
+        let u8 = LibRef::Named(Ty::<SemId>::U8.sem_id_unnamed());
+        let unit = LibRef::Named(SemId::unit());
+        let option = Ty::<LibRef>::union(variants!("none" => unit, "some" => u8.clone()));
+        let option_id = LibRef::Named(option.sem_id_unnamed());
+
+        let older =
+            sys([(None, Ty::U32), (Some("Rec"), Ty::Struct(fields!("amount" => u32.clone())))]);
+        let rec = TypeSymbol::with(older.resolve("Test.Rec").copied().unwrap(), "Test.Rec".into());
+        let optional = sys([
+            (None, Ty::U32),
+            (None, Ty::U8),
+            (None, Ty::UNIT),
+            (None, option),
+            (Some("Rec"), Ty::Struct(fields!("amount" => u32.clone(), "memo" => option_id))),
+        ]);
+        let issues = vec![Incompatibility::FieldRequired {
+            ty: rec,
+            field: fname!("memo"),
+        }];
+        assert_eq!(optional.is_compatible_with(&older), Err(issues.clone()));
+
+        let required = sys([
+            (None, Ty::U32),
+            (None, Ty::U8),
+            (Some("Rec"), Ty::Struct(fields!("amount" => u32, "memo" => u8))),
+        ]);
+        assert_eq!(required.is_compatible_with(&older), Err(issues));
+    }
+
+    #[test]
+    fn list_prefix() {
+        let u8 = LibRef::Named(Ty::<SemId>::U8.sem_id_unnamed());
+        let bytes = |sizing| sys([(None, Ty::U8), (Some("Bytes"), Ty::List(u8.clone(), sizing))]);
+        let older = bytes(Sizing::new(0, 200));
+        assert_eq!(bytes(Sizing::new(0, 255)).is_compatible_with(&older), Ok(()));
+
+        let newer = bytes(Sizing::U16);
+        assert_eq!(
+            newer.is_compatible_with(&older),
+            Err(vec![Incompatibility::PrefixChanged {
+                ty: TypeSymbol::with(
+                    older.resolve("Test.Bytes").copied().unwrap(),
+                    "Test.Bytes".into()
+                ),
+                old: 1,
+                new: 2,
+            }])
+        );
+    }
+
+    #[test]
+    fn narrowed_primitive() {
+        let older = sys([(Some("Amount"), Ty::U32)]);
+        let newer = sys([(Some("Amount"), Ty::U16)]);
+        assert_eq!(
+            newer.is_compatible_with(&older),
+            Err(vec![Incompatibility::PrimitiveChanged {
+                ty: TypeSymbol::with(
+                    Ty::<LibRef>::U32.sem_id_named(&tn!("Amount")),
+                    "Test.Amount".into()
+                ),
+                old: Primitive::U32,
+                new: Primitive::U16,
+            }])
+        );
+    }
+}
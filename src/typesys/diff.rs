@@ -0,0 +1,220 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{self, Display, Formatter};
+
+use crate::typesys::{SymbolicSys, TypeFqn, TypeSymbol};
+use crate::SemId;
+
+/// Structural difference between two versions of a type system.
+///
+/// Since semantic type ids commit to the type definitions, a type changing its definition while
+/// keeping its fully qualified name gets a new id; such types are reported as `changed`.
+///
+/// Semantic ids of named types commit to the type name (but not to the library name), so a type
+/// can keep its id under a different fully qualified name only when it moves to another library
+/// under the same name; such types are reported as `renamed`. A type which has changed its name
+/// gets a new id, and is reported as both `removed` and `added`. Named types which became
+/// anonymous are reported as `removed`.
+#[derive(Clone, Eq, PartialEq, Debug, Default)]
+pub struct TypeSystemDiff {
+    /// Types present only in the newer type system.
+    pub added: BTreeSet<TypeSymbol>,
+    /// Types present only in the older type system.
+    pub removed: BTreeSet<TypeSymbol>,
+    /// Named types which have changed their definitions, with the old and new semantic ids.
+    pub changed: BTreeMap<TypeFqn, (SemId, SemId)>,
+    /// Types with the same definition which have moved to a different library, with the old and
+    /// new fully qualified names.
+    pub renamed: BTreeMap<SemId, (TypeFqn, TypeFqn)>,
+}
+
+impl TypeSystemDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.changed.is_empty()
+            && self.renamed.is_empty()
+    }
+}
+
+impl Display for TypeSystemDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for sym in &self.added {
+            writeln!(f, "+ {sym} {:-}", sym.id)?;
+        }
+        for sym in &self.removed {
+            writeln!(f, "- {sym} {:-}", sym.id)?;
+        }
+        for (fqn, (old, new)) in &self.changed {
+            writeln!(f, "~ {fqn} {old:-} -> {new:-}")?;
+        }
+        for (id, (old, new)) in &self.renamed {
+            writeln!(f, "~ {old} -> {new} {id:-}")?;
+        }
+        Ok(())
+    }
+}
+
+impl SymbolicSys {
+    /// Computes structural difference between this type system and its `newer` version.
+    pub fn diff(&self, newer: &SymbolicSys) -> TypeSystemDiff {
+        let old_names = named(self);
+        let new_names = named(newer);
+
+        let mut diff = TypeSystemDiff::default();
+        let mut seen = BTreeSet::new();
+        for (fqn, old_id) in &old_names {
+            match new_names.get(fqn) {
+                Some(new_id) if new_id == old_id => {}
+                Some(new_id) => {
+                    diff.changed.insert((*fqn).clone(), (*old_id, *new_id));
+                    seen.extend([*old_id, *new_id]);
+                }
+                None => match newer.lookup(*old_id) {
+                    Some(new_fqn) => {
+                        diff.renamed.insert(*old_id, ((*fqn).clone(), new_fqn.clone()));
+                        seen.insert(*old_id);
+                    }
+                    // The type became anonymous, which is not reported by the id comparison below
+                    None if newer.as_types().get(*old_id).is_some() => {
+                        diff.removed.insert(TypeSymbol::with(*old_id, (*fqn).clone()));
+                    }
+                    None => {}
+                },
+            }
+        }
+
+        let old_ids = self.as_types().keys().collect::<BTreeSet<_>>();
+        let new_ids = newer.as_types().keys().collect::<BTreeSet<_>>();
+        for id in new_ids.difference(&old_ids).filter(|id| !seen.contains(**id)) {
+            diff.added.insert(symbol(newer, **id));
+        }
+        for id in old_ids.difference(&new_ids).filter(|id| !seen.contains(**id)) {
+            diff.removed.insert(symbol(self, **id));
+        }
+        diff
+    }
+}
+
+fn named(sys: &SymbolicSys) -> BTreeMap<&TypeFqn, SemId> {
+    sys.symbols.iter().filter_map(|sym| sym.fqn.as_ref().map(|fqn| (fqn, sym.id))).collect()
+}
+
+fn symbol(sys: &SymbolicSys, id: SemId) -> TypeSymbol {
+    match sys.lookup(id) {
+        Some(fqn) => TypeSymbol::with(id, fqn.clone()),
+        None => TypeSymbol::unnamed(id),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+
+    use encoding::{LibName, Primitive, TypeName};
+
+    use super::*;
+    use crate::typelib::LibRef;
+    use crate::typesys::SymTy;
+    use crate::Ty;
+
+    fn named_id(name: &'static str, prim: Primitive) -> SemId {
+        Ty::<LibRef>::Primitive(prim).sem_id_named(&TypeName::from(name))
+    }
+
+    fn sys(types: &[(&'static str, &'static str, Primitive)]) -> SymbolicSys {
+        let map = types
+            .iter()
+            .map(|(lib, name, prim)| {
+                let ty = Ty::Primitive(*prim);
+                let sym = SymTy::named(LibName::from(*lib), TypeName::from(*name), ty);
+                (named_id(name, *prim), sym)
+            })
+            .collect::<BTreeMap<_, _>>();
+        SymbolicSys::with(None, map).unwrap()
+    }
+
+    #[test]
+    fn trichotomy() {
+        let older = sys(&[
+            ("Test", "Same", Primitive::U8),
+            ("Test", "Changed", Primitive::U16),
+            ("Test", "Removed", Primitive::U32),
+            ("Old", "Moved", Primitive::U24),
+        ]);
+        let newer = sys(&[
+            ("Test", "Same", Primitive::U8),
+            ("Test", "Changed", Primitive::U64),
+            ("Test", "Added", Primitive::U128),
+            ("New", "Moved", Primitive::U24),
+        ]);
+        let diff = older.diff(&newer);
+
+        let id = |sys: &SymbolicSys, fqn| *sys.resolve(fqn).unwrap();
+        assert_eq!(diff.added, bset![TypeSymbol::with(
+            id(&newer, "Test.Added"),
+            "Test.Added".into()
+        )]);
+        assert_eq!(diff.removed, bset![TypeSymbol::with(
+            id(&older, "Test.Removed"),
+            "Test.Removed".into()
+        )]);
+        assert_eq!(
+            diff.changed,
+            bmap! { TypeFqn::from("Test.Changed") => (id(&older, "Test.Changed"), id(&newer, "Test.Changed")) }
+        );
+        assert_eq!(
+            diff.renamed,
+            bmap! { id(&older, "Old.Moved") => (TypeFqn::from("Old.Moved"), TypeFqn::from("New.Moved")) }
+        );
+        assert!(older.diff(&older).is_empty());
+    }
+
+    #[test]
+    fn renamed_type() {
+        let older = sys(&[("Test", "Before", Primitive::U8)]);
+        let newer = sys(&[("Test", "After", Primitive::U8)]);
+        let diff = older.diff(&newer);
+        assert!(diff.renamed.is_empty());
+        assert_eq!(diff.removed, bset![TypeSymbol::with(
+            named_id("Before", Primitive::U8),
+            "Test.Before".into()
+        )]);
+        assert_eq!(diff.added, bset![TypeSymbol::with(
+            named_id("After", Primitive::U8),
+            "Test.After".into()
+        )]);
+    }
+
+    #[test]
+    fn became_anonymous() {
+        let older = sys(&[("Test", "Named", Primitive::U8)]);
+        let id = named_id("Named", Primitive::U8);
+        let newer = SymbolicSys::with(None, bmap! { id => SymTy::unnamed(Ty::U8) }).unwrap();
+        let diff = older.diff(&newer);
+        assert_eq!(diff.removed, bset![TypeSymbol::with(id, "Test.Named".into())]);
+        assert!(diff.added.is_empty());
+        assert!(diff.renamed.is_empty());
+    }
+}
@@ -103,6 +103,9 @@ pub enum NestedCase {
 
     #[strict_type(tag = 0x13)]
     RStr(Option<TypeFqn>, Option<TypeFqn>, Sizing),
+
+    #[strict_type(tag = 0x20)]
+    BitFlags,
 }
 
 /*
@@ -137,6 +140,76 @@ impl<'sys> TypeInfo<'sys> {
 }
  */
 
+/// Layout role of a type, shared by [`TypeTreeIter`] and [`crate::TypeSystem::type_info`].
+pub(super) struct Nesting {
+    /// Nested case introduced by the type.
+    pub case: Option<NestedCase>,
+    /// Whether items of the type are placed one level deeper than the type itself.
+    pub dive: bool,
+    /// Whether items of the type are iterated over.
+    pub push: bool,
+    /// Whether the type is reported by itself, or only adds its nested case to the inner type.
+    pub ret: bool,
+    /// Number of leading items of the type which are not iterated over.
+    pub skip: usize,
+}
+
+/// Detects the layout role of a type. Types referenced by the `ty` are resolved with `get`, and
+/// their names are looked up with `lookup`; `fqn` is the name of the type itself.
+pub(super) fn nesting<'sys>(
+    ty: &Ty<SemId>,
+    fqn: Option<&TypeFqn>,
+    get: impl Fn(SemId) -> Option<&'sys Ty<SemId>>,
+    lookup: impl Fn(SemId) -> Option<TypeFqn>,
+) -> Nesting {
+    let mut nesting = Nesting {
+        case: None,
+        dive: true,
+        push: true,
+        ret: true,
+        skip: 0,
+    };
+    if ty.is_newtype() {
+        nesting.case = Some(NestedCase::NewType(fqn.cloned()));
+        nesting.dive = false;
+        nesting.ret = false;
+    } else if ty.is_option() {
+        nesting.case = Some(NestedCase::Option);
+        nesting.skip = 1; // skipping none
+        nesting.ret = false;
+    } else if let Ty::Tuple(fields) = ty {
+        if fields.len() == 2 {
+            if let Some((first, Ty::List(rest, sizing))) = get(fields[0]).zip(get(fields[1])) {
+                let other = get(*rest);
+                if first.is_char_enum() && other.map(Ty::is_char_enum).unwrap_or_default() {
+                    let mut sizing = *sizing;
+                    sizing.min += 1;
+                    sizing.max += 1;
+                    nesting.skip = 2; // skipping first char and nested list
+                    nesting.case = Some(NestedCase::RStr(lookup(fields[0]), lookup(*rest), sizing));
+                }
+            }
+        }
+    } else if let Ty::List(inner_id, _) | Ty::Array(inner_id, _) = ty {
+        // A lone unicode or ascii character is not a string, only a sequence of them is
+        let inner_ty = get(*inner_id);
+        if inner_ty.is_some_and(Ty::is_char_enum) {
+            nesting.case = Some(NestedCase::AsciiStr(lookup(*inner_id)));
+        } else if inner_ty.is_some_and(Ty::is_byte) {
+            nesting.case = Some(NestedCase::ByteStr);
+        } else if inner_ty.is_some_and(Ty::is_unicode_char) {
+            nesting.case = Some(NestedCase::UniStr);
+        }
+        if nesting.case.is_some() {
+            nesting.push = false;
+            nesting.dive = false;
+        }
+    } else if ty.is_bit_flags() {
+        nesting.case = Some(NestedCase::BitFlags);
+    }
+    nesting
+}
+
 pub struct TypeTreeIter<'sys> {
     sem_id: SemId,
     ty: Option<&'sys Ty<SemId>>,
@@ -155,54 +228,16 @@ impl<'sys> Iterator for TypeTreeIter<'sys> {
             let fqn = self.sys.symbols.lookup(self.sem_id);
             self.ty = None;
 
-            let mut nested = vec![];
-            let mut dive = true;
-            let mut push = true;
-            let mut ret = true;
+            let Nesting {
+                case,
+                dive,
+                push,
+                ret,
+                skip,
+            } = nesting(ty, fqn, |id| self.sys.get(id), |id| self.sys.symbols.lookup(id).cloned());
             let mut iter = ty.iter();
-
-            if ty.is_newtype() {
-                nested.push(NestedCase::NewType(fqn.cloned()));
-                dive = false;
-                ret = false;
-            } else if ty.is_option() {
-                nested.push(NestedCase::Option);
-                let _ = iter.next(); // skipping none
-                ret = false;
-            } else if let Ty::Tuple(fields) = ty {
-                if fields.len() == 2 {
-                    let first = self.sys.get(fields[0]);
-                    let rest = self.sys.get(fields[1]);
-                    if let Some((first, Ty::List(rest, sizing))) = first.zip(rest) {
-                        let other = self.sys.get(*rest);
-                        if first.is_char_enum() && other.map(Ty::is_char_enum).unwrap_or_default() {
-                            let first = self.sys.symbols.lookup(fields[0]);
-                            let rest = self.sys.symbols.lookup(*rest);
-                            let mut sizing = *sizing;
-                            sizing.min += 1;
-                            sizing.max += 1;
-                            let _ = iter.next(); // skipping first char
-                            let _ = iter.next(); // skipping nested list
-                            nested.push(NestedCase::RStr(first.cloned(), rest.cloned(), sizing))
-                        }
-                    }
-                }
-            } else if let Ty::List(inner_id, _) = ty {
-                let inner_ty = self.sys.get(*inner_id).expect("incomplete type system");
-                if inner_ty.is_char_enum() {
-                    let fqn = self.sys.symbols.lookup(*inner_id);
-                    nested.push(NestedCase::AsciiStr(fqn.cloned()));
-                } else if inner_ty.is_byte() {
-                    nested.push(NestedCase::ByteStr);
-                } else if inner_ty.is_unicode_char() {
-                    nested.push(NestedCase::UniStr);
-                }
-                if !nested.is_empty() {
-                    push = false;
-                    dive = false;
-                }
-            } else if ty.is_byte_array() {
-                push = false;
+            for _ in 0..skip {
+                let _ = iter.next();
             }
 
             let depth = self.depth;
@@ -212,7 +247,7 @@ impl<'sys> Iterator for TypeTreeIter<'sys> {
             if push {
                 self.path.push((self.depth, iter));
             }
-            self.nested.extend(nested);
+            self.nested.extend(case);
             if ret {
                 let mut item = None;
                 swap(&mut item, &mut self.item);
@@ -25,9 +25,15 @@ mod translate;
 mod id;
 mod symbols;
 mod iter;
+mod path;
+mod diff;
+mod compat;
 
+pub use compat::{sizing_is_wider, Incompatibility};
+pub use diff::TypeSystemDiff;
 pub use id::TypeSysId;
 pub use iter::{NestedCase, TypeInfo, TypeTree, TypeTreeIter};
+pub use path::PathError;
 pub use symbols::{SymbolicSys, Symbols};
 pub use translate::{Error, SystemBuilder, TypeSymbol};
-pub use type_sys::{SymTy, TypeFqn, TypeSystem, UnknownType};
+pub use type_sys::{Checkpoint, SymTy, TypeFqn, TypeFqnParseError, TypeSystem, UnknownType};
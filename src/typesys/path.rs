@@ -0,0 +1,162 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Resolution of textual field paths (like `order.items[].price`) against a type system.
+
+use crate::{SemId, Ty, TypeSystem};
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum PathError {
+    /// path component `{0}` has an invalid syntax.
+    InvalidSyntax(String),
+
+    /// type `{0}` referenced by the path is not a part of the type system.
+    UnknownType(SemId),
+
+    /// path component `{component}` can't be resolved inside {kind} type `{ty}`.
+    UnresolvedComponent {
+        component: String,
+        kind: &'static str,
+        ty: SemId,
+    },
+}
+
+impl TypeSystem {
+    /// Resolves a dotted field path, starting from the `root` type, into the semantic id of the
+    /// type located at that path.
+    ///
+    /// Each dot-separated component names either a struct field or a union variant. Components
+    /// may be suffixed with one or more bracket groups: `[]` steps into the element of an array,
+    /// list or set; any bracket group (like `[]` or `[key]`) applied to a map steps into the map
+    /// value type.
+    pub fn resolve_path(&self, root: SemId, path: &str) -> Result<SemId, PathError> {
+        let mut id = root;
+        if path.is_empty() {
+            return Ok(id);
+        }
+        for component in path.split('.') {
+            let (name, mut brackets) = match component.find('[') {
+                Some(pos) => component.split_at(pos),
+                None => (component, ""),
+            };
+            if name.is_empty() && brackets.is_empty() {
+                return Err(PathError::InvalidSyntax(component.to_owned()));
+            }
+            if !name.is_empty() {
+                id = self.resolve_name(id, name)?;
+            }
+            while !brackets.is_empty() {
+                let Some(end) = brackets.find(']').filter(|_| brackets.starts_with('[')) else {
+                    return Err(PathError::InvalidSyntax(component.to_owned()));
+                };
+                let (group, rest) = brackets.split_at(end + 1);
+                id = self.resolve_elem(id, group)?;
+                brackets = rest;
+            }
+        }
+        Ok(id)
+    }
+
+    fn resolve_name(&self, id: SemId, name: &str) -> Result<SemId, PathError> {
+        let ty = self.get(id).ok_or(PathError::UnknownType(id))?;
+        let found = match ty {
+            Ty::Struct(fields) => fields.iter().find(|f| f.name.as_str() == name).map(|f| f.ty),
+            Ty::Union(variants) => {
+                variants.iter().find(|(v, _)| v.name.as_str() == name).map(|(_, ty)| *ty)
+            }
+            _ => None,
+        };
+        found.ok_or_else(|| PathError::UnresolvedComponent {
+            component: name.to_owned(),
+            kind: ty.kind_str(),
+            ty: id,
+        })
+    }
+
+    fn resolve_elem(&self, id: SemId, group: &str) -> Result<SemId, PathError> {
+        let ty = self.get(id).ok_or(PathError::UnknownType(id))?;
+        let found = match ty {
+            Ty::Array(elem, _) | Ty::List(elem, _) | Ty::Set(elem, _) if group == "[]" => {
+                Some(*elem)
+            }
+            Ty::Map(_, val, _) => Some(*val),
+            _ => None,
+        };
+        found.ok_or_else(|| PathError::UnresolvedComponent {
+            component: group.to_owned(),
+            kind: ty.kind_str(),
+            ty: id,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use strict_encoding::Sizing;
+
+    use super::*;
+
+    fn order_system() -> (TypeSystem, SemId, SemId) {
+        let price = Ty::<SemId>::U64;
+        let price_id = price.sem_id_unnamed();
+        let item = Ty::<SemId>::Struct(fields!("price" => price_id));
+        let item_id = item.sem_id_unnamed();
+        let items = Ty::<SemId>::List(item_id, Sizing::U8);
+        let items_id = items.sem_id_unnamed();
+        let order = Ty::<SemId>::Struct(fields!("items" => items_id));
+        let order_id = order.sem_id_unnamed();
+        let shop = Ty::<SemId>::Struct(fields!("order" => order_id));
+        let shop_id = shop.sem_id_unnamed();
+
+        let mut sys = TypeSystem::new();
+        for ty in [price, item, items, order, shop] {
+            sys.insert_unchecked(ty.sem_id_unnamed(), ty).unwrap();
+        }
+        (sys, shop_id, price_id)
+    }
+
+    #[test]
+    fn nested_struct() {
+        let (sys, root, price) = order_system();
+        assert_eq!(sys.resolve_path(root, "order.items[].price"), Ok(price));
+        assert_eq!(sys.resolve_path(root, ""), Ok(root));
+    }
+
+    #[test]
+    fn invalid_field() {
+        let (sys, root, _) = order_system();
+        let order = sys.resolve_path(root, "order").unwrap();
+        assert_eq!(
+            sys.resolve_path(root, "order.lines[].price"),
+            Err(PathError::UnresolvedComponent {
+                component: s!("lines"),
+                kind: "struct",
+                ty: order,
+            })
+        );
+        assert_eq!(
+            sys.resolve_path(root, "order.items[.price"),
+            Err(PathError::InvalidSyntax(s!("items[")))
+        );
+    }
+}
@@ -25,8 +25,9 @@ use std::fmt::{self, Display, Formatter};
 use std::ops::Index;
 
 use amplify::confinement::{self, MediumOrdSet, SmallOrdSet};
-use encoding::{StrictDeserialize, StrictSerialize, STRICT_TYPES_LIB};
+use encoding::{LibName, StrictDeserialize, StrictSerialize, TypeName, STRICT_TYPES_LIB};
 
+use crate::layout::vesper::TypeVesper;
 use crate::typesys::{translate, SymTy, TypeFqn, TypeSymbol, TypeSysId, TypeTree};
 use crate::typify::TypeSpec;
 use crate::{Dependency, SemId, Translate, Ty, TypeSystem};
@@ -77,8 +78,26 @@ impl Symbols {
         self.symbols.iter().find(|fqid| fqid.fqn.as_ref() == Some(&needle)).map(|fqid| &fqid.id)
     }
 
+    /// Finds name of the type with the given semantic id. If there are multiple symbols for the
+    /// same id, the first named one is returned.
     pub fn lookup(&self, sem_id: SemId) -> Option<&TypeFqn> {
-        self.symbols.iter().find(|sym| sym.id == sem_id).and_then(|sym| sym.fqn.as_ref())
+        self.symbols.iter().filter(|sym| sym.matches_id(sem_id)).find_map(|sym| sym.fqn.as_ref())
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &TypeSymbol> { self.symbols.iter() }
+
+    /// Leaves a single symbol per semantic id, preferring named symbols over anonymous ones.
+    pub fn canonicalize(&mut self) {
+        let mut canonical = BTreeMap::<SemId, TypeSymbol>::new();
+        for sym in self.symbols.iter() {
+            match canonical.get(&sym.id) {
+                Some(present) if present.fqn.is_some() || sym.fqn.is_none() => {}
+                _ => {
+                    canonical.insert(sym.id, sym.clone());
+                }
+            }
+        }
+        self.symbols = MediumOrdSet::from_iter_checked(canonical.into_values());
     }
 }
 
@@ -140,6 +159,12 @@ impl SymbolicSys {
 
     pub fn new(types: TypeSystem, symbols: Symbols) -> Self { Self { symbols, types } }
 
+    /// Removes duplicated symbols for the same type, keeping a single one per semantic id and
+    /// preferring named symbols. Types are referenced by their semantic ids, thus the type
+    /// system itself is not changed.
+    #[inline]
+    pub fn canonicalize(&mut self) { self.symbols.canonicalize() }
+
     pub fn id(&self) -> TypeSysId { self.types.id() }
 
     pub fn get(&self, spec: impl Into<TypeSpec>) -> Option<&Ty<SemId>> {
@@ -164,16 +189,79 @@ impl SymbolicSys {
         }
     }
 
+    /// Iterates over named types only, skipping anonymous ones.
+    pub fn named_types(&self) -> impl Iterator<Item = (&TypeFqn, SemId, &Ty<SemId>)> {
+        self.symbols.iter().filter_map(|sym| {
+            let fqn = sym.fqn.as_ref()?;
+            let ty = self.types.get(sym.id)?;
+            Some((fqn, sym.id, ty))
+        })
+    }
+
+    /// Groups named types by the library they originate from. Anonymous types are omitted.
+    pub fn by_library(&self) -> BTreeMap<LibName, Vec<(TypeName, SemId)>> {
+        let mut libs = BTreeMap::<LibName, Vec<(TypeName, SemId)>>::new();
+        for (fqn, id, _) in self.named_types() {
+            libs.entry(fqn.lib.clone()).or_default().push((fqn.name.clone(), id));
+        }
+        libs
+    }
+
+    /// Converts each type of the system into its Vesper layout, resolving all nested types
+    /// against the system.
+    pub fn to_vesper_tree(&self) -> Vec<TypeVesper> {
+        self.types.keys().map(|id| TypeTree::new(*id, self).to_layout().to_vesper()).collect()
+    }
+
     pub fn into_type_system(self) -> TypeSystem { self.types }
+
+    /// Converts the type system into JSON object, where the types are keyed by their fully
+    /// qualified names, or by semantic ids for anonymous types.
+    ///
+    /// Unlike the serde serialization of the type system, which is keyed by semantic ids, this
+    /// representation is intended for human inspection and can't be deserialized back.
+    #[cfg(feature = "serde")]
+    pub fn to_json(&self) -> serde_json::Value {
+        serde_json::Value::Object(
+            self.types
+                .iter()
+                .map(|(id, ty)| {
+                    let key = match self.lookup(*id) {
+                        Some(fqn) => fqn.to_string(),
+                        None => id.to_string(),
+                    };
+                    let ty = serde_json::to_value(ty).expect("types are always serializable");
+                    (key, ty)
+                })
+                .collect(),
+        )
+    }
 }
 
+/// Alternate formatting (`{:#}`) lists named types sorted by their fully qualified names,
+/// followed by anonymous types sorted by their semantic ids.
 impl Display for SymbolicSys {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         writeln!(f, "typesys -- {:+}", self.id())?;
         writeln!(f)?;
-        for (id, ty) in self.types.as_unconfined() {
+        // Resolving names once, since each lookup scans all symbols; the first named symbol for
+        // an id wins, as in `lookup`
+        let mut names = BTreeMap::<SemId, &TypeFqn>::new();
+        for sym in self.symbols.iter() {
+            if let Some(fqn) = &sym.fqn {
+                names.entry(sym.id).or_insert(fqn);
+            }
+        }
+        let mut types = self.types.as_unconfined().iter().collect::<Vec<_>>();
+        if f.alternate() {
+            types.sort_by_key(|(id, _)| {
+                let fqn = names.get(*id);
+                (fqn.is_none(), fqn.copied(), **id)
+            });
+        }
+        for (id, ty) in types {
             let ty = ty.clone().translate(&mut (), self).expect("type system inconsistency");
-            match self.lookup(*id) {
+            match names.get(id) {
                 Some(fqn) => {
                     writeln!(f, "-- {id:-}")?;
                     writeln!(f, "data {fqn}: {ty:-}")?;
@@ -192,3 +280,151 @@ impl armor::StrictArmor for SymbolicSys {
 
     fn armor_id(&self) -> Self::Id { self.id() }
 }
+
+#[cfg(test)]
+mod test {
+    use encoding::Sizing;
+
+    use super::*;
+
+    #[test]
+    fn named_types() {
+        let types = [
+            SymTy::named(LibName::from("Test"), tn!("Named"), Ty::U8),
+            SymTy::unnamed(Ty::U16),
+            SymTy::named(LibName::from("Test"), tn!("Other"), Ty::U32),
+        ]
+        .into_iter()
+        .map(|sym| (sym.ty.sem_id_unnamed(), sym))
+        .collect::<BTreeMap<_, _>>();
+        let sys = SymbolicSys::with(None, types).unwrap();
+
+        let mut named =
+            sys.named_types().map(|(fqn, _, ty)| (fqn.clone(), ty.clone())).collect::<Vec<_>>();
+        named.sort_by(|a, b| a.0.cmp(&b.0));
+        assert_eq!(named, vec![
+            (TypeFqn::from("Test.Named"), Ty::U8),
+            (TypeFqn::from("Test.Other"), Ty::U32)
+        ]);
+    }
+
+    #[test]
+    fn matches_id() {
+        let id = Ty::<SemId>::U8.sem_id_unnamed();
+        let named = TypeSymbol::with(id, TypeFqn::from("Test.Named"));
+        let unnamed = TypeSymbol::unnamed(id);
+        assert_ne!(named, unnamed);
+        assert!(named.matches_id(id));
+        assert!(unnamed.matches_id(id));
+        assert!(!named.matches_id(Ty::<SemId>::U16.sem_id_unnamed()));
+
+        let mut symbols = Symbols::with(None).unwrap();
+        symbols.update_unchecked(id, None).unwrap();
+        symbols.update_unchecked(id, Some(TypeFqn::from("Test.Named"))).unwrap();
+        assert_eq!(symbols.lookup(id), Some(&TypeFqn::from("Test.Named")));
+    }
+
+    #[test]
+    fn canonicalize() {
+        let id = Ty::<SemId>::U8.sem_id_unnamed();
+        let other = Ty::<SemId>::U16.sem_id_unnamed();
+        let mut symbols = Symbols::with(None).unwrap();
+        symbols.update_unchecked(id, None).unwrap();
+        symbols.update_unchecked(id, Some(TypeFqn::from("Test.Named"))).unwrap();
+        symbols.update_unchecked(other, None).unwrap();
+        assert_eq!(symbols.iter().count(), 3);
+
+        let mut types = TypeSystem::new();
+        types.insert_unchecked(id, Ty::U8).unwrap();
+        types.insert_unchecked(other, Ty::U16).unwrap();
+        let mut sys = SymbolicSys::new(types, symbols);
+        sys.canonicalize();
+        assert_eq!(sys.as_symbols().iter().count(), 2);
+        assert_eq!(sys.lookup(id), Some(&TypeFqn::from("Test.Named")));
+        assert_eq!(sys.lookup(other), None);
+    }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn to_json() {
+        let types =
+            [SymTy::named(LibName::from("Test"), tn!("Named"), Ty::U8), SymTy::unnamed(Ty::U16)]
+                .into_iter()
+                .map(|sym| (sym.ty.sem_id_unnamed(), sym))
+                .collect::<BTreeMap<_, _>>();
+        let sys = SymbolicSys::with(None, types).unwrap();
+
+        let json = sys.to_json();
+        let object = json.as_object().unwrap();
+        assert_eq!(object.len(), 2);
+        assert_eq!(object["Test.Named"], serde_json::to_value(Ty::<SemId>::U8).unwrap());
+        assert!(object.contains_key(&Ty::<SemId>::U16.sem_id_unnamed().to_string()));
+    }
+
+    #[test]
+    fn display_sorted() {
+        let types = [
+            SymTy::named(LibName::from("Test"), tn!("Zeta"), Ty::U8),
+            SymTy::unnamed(Ty::U64),
+            SymTy::named(LibName::from("Test"), tn!("Alpha"), Ty::U16),
+            SymTy::named(LibName::from("Other"), tn!("Mid"), Ty::U32),
+        ]
+        .into_iter()
+        .map(|sym| (sym.ty.sem_id_unnamed(), sym))
+        .collect::<BTreeMap<_, _>>();
+        let sys = SymbolicSys::with(None, types).unwrap();
+
+        let dump = format!("{sys:#}");
+        let anonymous = format!("data {:-}", Ty::<SemId>::U64.sem_id_unnamed());
+        let order = ["data Other.Mid", "data Test.Alpha", "data Test.Zeta", &anonymous]
+            .map(|needle| dump.find(needle).expect("type is present"));
+        assert!(order.windows(2).all(|w| w[0] < w[1]));
+    }
+
+    #[test]
+    fn by_library() {
+        let types = [
+            SymTy::named(LibName::from("First"), tn!("Alpha"), Ty::U8),
+            SymTy::named(LibName::from("First"), tn!("Beta"), Ty::U16),
+            SymTy::named(LibName::from("Second"), tn!("Gamma"), Ty::U32),
+            SymTy::unnamed(Ty::U64),
+        ]
+        .into_iter()
+        .map(|sym| (sym.ty.sem_id_unnamed(), sym))
+        .collect::<BTreeMap<_, _>>();
+        let sys = SymbolicSys::with(None, types).unwrap();
+
+        let mut libs = sys.by_library();
+        libs.values_mut().for_each(|types| types.sort());
+        assert_eq!(libs, bmap! {
+            LibName::from("First") => vec![
+                (tn!("Alpha"), Ty::<SemId>::U8.sem_id_unnamed()),
+                (tn!("Beta"), Ty::<SemId>::U16.sem_id_unnamed()),
+            ],
+            LibName::from("Second") => vec![(tn!("Gamma"), Ty::<SemId>::U32.sem_id_unnamed())]
+        });
+    }
+
+    #[test]
+    fn to_vesper_tree() {
+        let items = Ty::<SemId>::List(Ty::<SemId>::U16.sem_id_unnamed(), Sizing::U8);
+        let rec = Ty::<SemId>::Struct(fields!("items" => items.sem_id_unnamed()));
+        let types = [
+            SymTy::named(LibName::from("Test"), tn!("Rec"), rec),
+            SymTy::unnamed(items),
+            SymTy::unnamed(Ty::U16),
+        ]
+        .into_iter()
+        .map(|sym| (sym.ty.sem_id_unnamed(), sym))
+        .collect::<BTreeMap<_, _>>();
+        let sys = SymbolicSys::with(None, types).unwrap();
+
+        let tree = sys.to_vesper_tree();
+        assert_eq!(tree.len(), 3);
+        let rec = tree.iter().find(|expr| expr.subject.as_str() == "Rec").unwrap();
+        assert_eq!(
+            rec.display().to_string(),
+            "Rec rec\n  items list len=0..MAX8\n    element is U16\n"
+        );
+    }
+}
@@ -53,6 +53,10 @@ impl TypeSymbol {
             fqn: Some(TypeFqn::with(lib, name)),
         }
     }
+
+    /// Checks whether the symbol refers to the type with the provided semantic id, ignoring
+    /// the type name.
+    pub fn matches_id(&self, id: SemId) -> bool { self.id == id }
 }
 
 impl SemCommit for TypeSymbol {
@@ -26,14 +26,19 @@
 use std::collections::{BTreeMap, BTreeSet};
 use std::fmt::{self, Display, Formatter};
 use std::ops::Index;
+use std::str::FromStr;
 
 use amplify::confinement::{self, Confined, MediumOrdMap};
 use amplify::num::u24;
-use encoding::{LibName, Sizing, StrictDeserialize, StrictSerialize, TypeName};
+use encoding::{
+    FieldName, InvalidRString, LibName, Sizing, StrictDeserialize, StrictSerialize, TypeName,
+};
 use strict_encoding::STRICT_TYPES_LIB;
 
-use crate::ast::UnnamedFields;
-use crate::{SemId, Ty};
+use crate::ast::{Field, NamedFields, UnionVariants, UnnamedFields};
+use crate::typesys::iter::{nesting, Nesting};
+use crate::typesys::{NestedCase, TypeInfo};
+use crate::{Cls, SemId, Ty};
 
 #[derive(Copy, Clone, Ord, PartialOrd, Eq, PartialEq, Hash, Debug, Display, Error)]
 #[display("type with id `{0}` is not a part of the type system.")]
@@ -70,6 +75,46 @@ impl From<&'static str> for TypeFqn {
     }
 }
 
+/// Errors parsing fully qualified type name.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum TypeFqnParseError {
+    /// fully qualified type name `{0}` must contain library name separated by a dot.
+    NoLib(String),
+
+    /// invalid library name in `{0}`: {1}
+    InvalidLib(String, InvalidRString),
+
+    /// invalid type name in `{0}`: {1}
+    InvalidName(String, InvalidRString),
+}
+
+impl FromStr for TypeFqn {
+    type Err = TypeFqnParseError;
+
+    /// Parses fully qualified type name in `lib.name` form. The string is split on the last
+    /// dot, such that everything before it is treated as a library name and everything after
+    /// it - as a type name. Both parts must be valid identifiers.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let Some((lib, name)) = s.rsplit_once('.') else {
+            return Err(TypeFqnParseError::NoLib(s.to_owned()));
+        };
+        Ok(TypeFqn {
+            lib: LibName::from_str(lib)
+                .map_err(|err| TypeFqnParseError::InvalidLib(s.to_owned(), err))?,
+            name: TypeName::from_str(name)
+                .map_err(|err| TypeFqnParseError::InvalidName(s.to_owned(), err))?,
+        })
+    }
+}
+
+/// Snapshot of a type system state, created with [`TypeSystem::checkpoint`].
+///
+/// Since types are identified by their semantic ids and type system provides no way of removing
+/// or replacing types, the snapshot records just the ids of the types present in the system.
+#[derive(Clone, Eq, PartialEq, Debug)]
+pub struct Checkpoint(BTreeSet<SemId>);
+
 /// Type coupled with symbolic information.
 #[derive(Clone, Eq, PartialEq, Debug)]
 #[derive(StrictDumb, StrictType, StrictEncode, StrictDecode)]
@@ -121,8 +166,24 @@ impl StrictDeserialize for TypeSystem {}
 impl TypeSystem {
     pub fn new() -> Self { Self::default() }
 
+    /// Constructs type system from semantic ids and types, failing if the number of types
+    /// exceeds the type system limit of 2^24-1 types.
+    ///
+    /// The constructor doesn't check that the type system is complete.
+    pub fn try_from_iter(
+        iter: impl IntoIterator<Item = (SemId, Ty<SemId>)>,
+    ) -> Result<Self, confinement::Error> {
+        Confined::try_from_iter(iter).map(Self)
+    }
+
     pub fn count_types(&self) -> u24 { self.0.len_u24() }
 
+    #[inline]
+    pub fn len(&self) -> usize { self.0.len() }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool { self.0.is_empty() }
+
     pub(super) fn insert_unchecked(
         &mut self,
         sem_id: SemId,
@@ -133,10 +194,171 @@ impl TypeSystem {
 
     pub fn get(&self, sem_id: SemId) -> Option<&Ty<SemId>> { self.0.get(&sem_id) }
 
+    /// Assembles layout information about a type, detecting its nested case (newtype, option,
+    /// bit flags or one of string types). The returned information matches the first item of a
+    /// type tree iteration: newtypes and options are unwrapped, adding their nested case to the
+    /// inner type. Since the type system does not keep type names, the returned information
+    /// contains no fully qualified type names.
+    pub fn type_info(&self, id: SemId) -> Option<TypeInfo> {
+        let mut ty = self.get(id)?;
+        let mut depth = 0;
+        let mut item = None;
+        let mut nested = vec![];
+        loop {
+            let Nesting {
+                case,
+                dive,
+                ret,
+                skip,
+                ..
+            } = nesting(ty, None, |id| self.get(id), |_| None);
+            nested.extend(case);
+            if ret {
+                return Some(TypeInfo {
+                    depth,
+                    ty: ty.clone(),
+                    fqn: None,
+                    item,
+                    nested: Confined::from_checked(nested),
+                });
+            }
+            if dive {
+                depth += 1;
+            }
+            let (inner, case) = ty.iter().nth(skip)?;
+            if !matches!(nested.last(), Some(NestedCase::NewType(_))) {
+                item = case;
+            }
+            ty = self.get(*inner)?;
+        }
+    }
+
+    /// Computes size of the strict-serialized type system from the shapes of its types, without
+    /// running the actual serialization.
+    pub fn estimated_size(&self) -> usize {
+        const ID: usize = 32;
+        const SIZING: usize = 16;
+        // Identifiers are serialized with a single-byte length prefix
+        let ident = |s: &str| 1 + s.len();
+
+        // Prefix of the type map length
+        let mut size = 3;
+        for ty in self.0.values() {
+            size += ID + 1;
+            size += match ty {
+                Ty::Primitive(_) => 1,
+                Ty::UnicodeChar => 0,
+                Ty::Enum(variants) => {
+                    1 + variants.iter().map(|v| ident(v.name.as_str()) + 1).sum::<usize>()
+                }
+                Ty::Union(variants) => {
+                    1 + variants.keys().map(|v| ident(v.name.as_str()) + 1 + ID).sum::<usize>()
+                }
+                Ty::Tuple(fields) => 1 + fields.len() * ID,
+                Ty::Struct(fields) => {
+                    1 + fields.iter().map(|f| ident(f.name.as_str()) + ID).sum::<usize>()
+                }
+                Ty::Array(_, _) => ID + 2,
+                Ty::List(_, _) | Ty::Set(_, _) => ID + SIZING,
+                Ty::Map(_, _, _) => 2 * ID + SIZING,
+            };
+        }
+        size
+    }
+
+    /// Lists ids of all types directly referencing the type with the provided id.
+    pub fn referrers(&self, id: SemId) -> Vec<SemId> {
+        self.0.iter().filter(|(_, ty)| ty.contains_ref(&id)).map(|(sem_id, _)| *sem_id).collect()
+    }
+
+    /// Lists all structure fields having an optional type, as pairs of the structure type id
+    /// and the field name.
+    pub fn optional_fields(&self) -> Vec<(SemId, FieldName)> {
+        let mut fields = vec![];
+        for (id, ty) in &self.0 {
+            let Some(struct_fields) = ty.as_struct() else {
+                continue;
+            };
+            fields.extend(
+                struct_fields
+                    .iter()
+                    .filter(|field| self.get(field.ty).is_some_and(Ty::is_option))
+                    .map(|field| (*id, field.name.clone())),
+            );
+        }
+        fields
+    }
+
+    /// Collects distinct types used as map keys across the type system.
+    pub fn key_types(&self) -> BTreeSet<SemId> {
+        self.0.values().filter_map(Ty::map_key).copied().collect()
+    }
+
+    /// Builds index of all types directly referencing each of the types in the system. Types
+    /// which are not referenced by any other type are not present in the index.
+    pub fn reverse_deps(&self) -> BTreeMap<SemId, BTreeSet<SemId>> {
+        let mut index = BTreeMap::<SemId, BTreeSet<SemId>>::new();
+        for (id, ty) in &self.0 {
+            for (nested, _) in ty.type_refs() {
+                index.entry(*nested).or_default().insert(*id);
+            }
+        }
+        index
+    }
+
+    /// Counts number of types in the system per each type class.
+    pub fn class_histogram(&self) -> BTreeMap<Cls, usize> {
+        let mut histogram = BTreeMap::new();
+        for ty in self.0.values() {
+            *histogram.entry(ty.cls()).or_default() += 1;
+        }
+        histogram
+    }
+
     pub fn extend(&mut self, other: Self) -> Result<(), confinement::Error> {
         self.0.extend(other.0)
     }
 
+    /// Adds types to the type system, failing if the number of types exceeds the type system
+    /// limit of 2^24-1 types. Types added before the failure are kept.
+    pub fn extend_checked(
+        &mut self,
+        iter: impl IntoIterator<Item = (SemId, Ty<SemId>)>,
+    ) -> Result<(), confinement::Error> {
+        self.0.extend(iter)
+    }
+
+    /// Rewrites all semantic ids in the type system - both the ids of the types and the ids
+    /// used by the types to reference other types - using the provided function.
+    ///
+    /// If the function maps different ids to the same id, only one of the types under the
+    /// resulting id is preserved.
+    pub fn map_ids(self, f: impl Fn(SemId) -> SemId) -> TypeSystem {
+        let mut table = BTreeMap::new();
+        for (id, ty) in &self.0 {
+            table.entry(*id).or_insert_with(|| f(*id));
+            for (nested, _) in ty.iter() {
+                table.entry(*nested).or_insert_with(|| f(*nested));
+            }
+        }
+        let types = self.0.into_iter().map(|(id, ty)| (table[&id], remap(ty, &table)));
+        Self(Confined::from_iter_checked(types))
+    }
+
+    /// Records the current state of the type system, such that types added afterwards can be
+    /// rolled back with [`TypeSystem::restore`].
+    pub fn checkpoint(&self) -> Checkpoint { Checkpoint(self.0.keys().copied().collect()) }
+
+    /// Restores the type system to the state at the moment of the `checkpoint` creation,
+    /// removing all types added since then.
+    pub fn restore(&mut self, checkpoint: Checkpoint) {
+        let added =
+            self.0.keys().filter(|id| !checkpoint.0.contains(*id)).copied().collect::<Vec<_>>();
+        for id in added {
+            self.0.remove(&id).expect("type system has no minimal size");
+        }
+    }
+
     pub fn extract(&self, ids: impl IntoIterator<Item = SemId>) -> Result<Self, UnknownType> {
         let mut ids = ids.into_iter().collect::<BTreeSet<_>>();
         let mut found = BTreeSet::new();
@@ -181,6 +403,48 @@ impl TypeSystem {
     }
 }
 
+// We do not use `Translate` here since implementing `Translate<SemId>` for `SemId` breaks type
+// inference for all other `Ty<SemId>` translations.
+fn remap(ty: Ty<SemId>, table: &BTreeMap<SemId, SemId>) -> Ty<SemId> {
+    let id = |id: SemId| table[&id];
+    match ty {
+        Ty::Primitive(_) | Ty::UnicodeChar | Ty::Enum(_) => ty,
+        Ty::Union(variants) => Ty::Union(UnionVariants::from(Confined::from_iter_checked(
+            variants.into_iter().map(|(variant, ty)| (variant, id(ty))),
+        ))),
+        Ty::Struct(fields) => Ty::Struct(
+            NamedFields::try_from(
+                fields
+                    .into_iter()
+                    .map(|field| Field {
+                        name: field.name,
+                        ty: id(field.ty),
+                    })
+                    .collect::<Vec<_>>(),
+            )
+            .expect("re-packing existing fields structure"),
+        ),
+        Ty::Tuple(fields) => Ty::Tuple(
+            UnnamedFields::try_from(fields.into_iter().map(id).collect::<Vec<_>>())
+                .expect("re-packing existing fields structure"),
+        ),
+        Ty::Array(ty, len) => Ty::Array(id(ty), len),
+        Ty::List(ty, sizing) => Ty::List(id(ty), sizing),
+        Ty::Set(ty, sizing) => Ty::Set(id(ty), sizing),
+        Ty::Map(key, ty, sizing) => Ty::Map(id(key), id(ty), sizing),
+    }
+}
+
+/// # Panics
+///
+/// If the number of types exceeds the type system limit of 2^24-1 types. Use
+/// [`TypeSystem::extend_checked`] to handle the overflow.
+impl Extend<(SemId, Ty<SemId>)> for TypeSystem {
+    fn extend<T: IntoIterator<Item = (SemId, Ty<SemId>)>>(&mut self, iter: T) {
+        self.extend_checked(iter).expect("type system exceeds the maximal number of types")
+    }
+}
+
 impl Index<SemId> for TypeSystem {
     type Output = Ty<SemId>;
 
@@ -209,3 +473,287 @@ impl armor::StrictArmor for TypeSystem {
 
     fn armor_id(&self) -> Self::Id { self.id() }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn fqn_from_str() {
+        let fqn = TypeFqn::from_str("MyLib.MyType").unwrap();
+        assert_eq!(fqn, TypeFqn::with(libname!("MyLib"), tn!("MyType")));
+        assert_eq!(TypeFqn::from_str(&fqn.to_string()), Ok(fqn));
+        assert_eq!(TypeFqn::from_str("MyType"), Err(TypeFqnParseError::NoLib(s!("MyType"))));
+        assert!(matches!(
+            TypeFqn::from_str("My.Lib.MyType"),
+            Err(TypeFqnParseError::InvalidLib(..))
+        ));
+        assert!(matches!(TypeFqn::from_str("MyLib."), Err(TypeFqnParseError::InvalidName(..))));
+    }
+
+    #[test]
+    fn type_info() {
+        let char = Ty::<SemId>::Enum(variants!(32..=126));
+        let ascii = Ty::<SemId>::List(char.sem_id_unnamed(), Sizing::U8);
+        let ascii_id = ascii.sem_id_unnamed();
+        let sys =
+            TypeSystem::try_from_iter([char, ascii].map(|ty| (ty.sem_id_unnamed(), ty))).unwrap();
+
+        let info = sys.type_info(ascii_id).unwrap();
+        assert_eq!(info.depth, 0);
+        assert_eq!(info.fqn, None);
+        assert_eq!(info.nested.as_slice(), &[NestedCase::AsciiStr(None)]);
+        assert_eq!(sys.type_info(Ty::<SemId>::U8.sem_id_unnamed()), None);
+    }
+
+    #[test]
+    fn type_info_unicode() {
+        let char_id = Ty::<SemId>::UNICODE.sem_id_unnamed();
+        let string = Ty::<SemId>::List(char_id, Sizing::U8);
+        let string_id = string.sem_id_unnamed();
+        let ascii_char = Ty::<SemId>::Enum(variants!(32..=126));
+        let ascii = Ty::<SemId>::Array(ascii_char.sem_id_unnamed(), 4);
+        let ascii_id = ascii.sem_id_unnamed();
+        let sys = TypeSystem::try_from_iter(
+            [Ty::UNICODE, string, ascii_char, ascii].map(|ty| (ty.sem_id_unnamed(), ty)),
+        )
+        .unwrap();
+
+        let info = sys.type_info(string_id).unwrap();
+        assert_eq!(info.nested.as_slice(), &[NestedCase::UniStr]);
+        let info = sys.type_info(char_id).unwrap();
+        assert_eq!(info.ty, Ty::UNICODE);
+        assert!(info.nested.is_empty());
+        let info = sys.type_info(ascii_id).unwrap();
+        assert_eq!(info.nested.as_slice(), &[NestedCase::AsciiStr(None)]);
+    }
+
+    #[test]
+    fn type_info_matches_tree() {
+        use crate::typesys::SymTy;
+        use crate::{PrimitiveRef, SymbolicSys};
+
+        let bytes = Ty::<SemId>::List(SemId::byte(), Sizing::U8);
+        let bytes_id = bytes.sem_id_unnamed();
+        let wrapper = Ty::<SemId>::Struct(fields!("data" => bytes_id));
+        let wrapper_id = wrapper.sem_id_unnamed();
+        let option = Ty::<SemId>::option(wrapper_id);
+        let option_id = option.sem_id_unnamed();
+        let types = [Ty::UNIT, Ty::BYTE, bytes, wrapper, option]
+            .map(|ty| (ty.sem_id_unnamed(), SymTy::unnamed(ty)));
+        let sys = SymbolicSys::with(None, BTreeMap::from(types)).unwrap();
+
+        for id in [bytes_id, wrapper_id, option_id] {
+            let tree = sys.type_tree(id).unwrap();
+            assert_eq!(sys.as_types().type_info(id), tree.iter().next());
+        }
+        let info = sys.as_types().type_info(option_id).unwrap();
+        assert_eq!(info.nested.as_slice(), &[NestedCase::Option]);
+        assert_eq!(info.ty, Ty::Struct(fields!("data" => bytes_id)));
+    }
+
+    #[test]
+    fn try_from_iter() {
+        let types =
+            [Ty::U8, Ty::U16, Ty::Struct(fields!("first" => Ty::<SemId>::U8.sem_id_unnamed()))];
+        let sys =
+            TypeSystem::try_from_iter(types.iter().map(|ty| (ty.sem_id_unnamed(), ty.clone())))
+                .unwrap();
+        assert_eq!(sys.len(), 3);
+        for ty in types {
+            assert_eq!(sys.get(ty.sem_id_unnamed()), Some(&ty));
+        }
+    }
+
+    #[test]
+    fn try_from_iter_overflow() {
+        let count = u24::MAX.into_usize() + 1;
+        let types = (0..count as u32).map(|no| {
+            let mut id = [0u8; 32];
+            id[..4].copy_from_slice(&no.to_be_bytes());
+            (SemId::from(id), Ty::UNIT)
+        });
+        assert_eq!(TypeSystem::try_from_iter(types).unwrap_err(), confinement::Error::Oversize {
+            len: count,
+            max_len: u24::MAX.into_usize()
+        });
+    }
+
+    #[test]
+    fn extend() {
+        let types = [Ty::<SemId>::U8, Ty::U16];
+        let mut sys = TypeSystem::new();
+        Extend::extend(&mut sys, types.iter().map(|ty| (ty.sem_id_unnamed(), ty.clone())));
+        assert_eq!(sys.len(), 2);
+        sys.extend_checked([(Ty::<SemId>::U32.sem_id_unnamed(), Ty::U32)]).unwrap();
+        assert_eq!(sys.len(), 3);
+        assert_eq!(sys.get(Ty::<SemId>::U32.sem_id_unnamed()), Some(&Ty::U32));
+    }
+
+    #[test]
+    fn len() {
+        let mut sys = TypeSystem::new();
+        assert!(sys.is_empty());
+        assert_eq!(sys.len(), 0);
+        sys.insert_unchecked(Ty::<SemId>::U8.sem_id_unnamed(), Ty::U8).unwrap();
+        sys.insert_unchecked(Ty::<SemId>::U16.sem_id_unnamed(), Ty::U16).unwrap();
+        assert!(!sys.is_empty());
+        assert_eq!(sys.len(), 2);
+    }
+
+    #[test]
+    fn estimated_size() {
+        use amplify::confinement::U24 as U24MAX;
+
+        use crate::stl::{std_stl, strict_types_stl};
+        use crate::SystemBuilder;
+
+        let sys = SystemBuilder::new()
+            .import(std_stl())
+            .unwrap()
+            .import(strict_types_stl())
+            .unwrap()
+            .finalize()
+            .unwrap()
+            .into_type_system();
+        let real = sys.to_strict_serialized::<U24MAX>().unwrap().len();
+        assert_eq!(sys.estimated_size(), real);
+    }
+
+    #[test]
+    fn referrers() {
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let strct = Ty::<SemId>::Struct(fields!("first" => u8));
+        let list = Ty::<SemId>::List(u8, Sizing::U8);
+        let other = Ty::<SemId>::Set(Ty::<SemId>::U16.sem_id_unnamed(), Sizing::U8);
+        let mut sys = TypeSystem::new();
+        for ty in [Ty::U8, Ty::U16, strct.clone(), list.clone(), other] {
+            sys.insert_unchecked(ty.sem_id_unnamed(), ty).unwrap();
+        }
+        let mut expected = vec![strct.sem_id_unnamed(), list.sem_id_unnamed()];
+        expected.sort();
+        assert_eq!(sys.referrers(u8), expected);
+    }
+
+    #[test]
+    fn optional_fields() {
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let option = Ty::<SemId>::Union(variants!("none" => SemId::unit(), "some" => u8));
+        let strct = Ty::<SemId>::Struct(fields!(
+            "required" => u8,
+            "optional" => option.sem_id_unnamed()
+        ));
+        let types = [Ty::UNIT, Ty::U8, option, strct.clone()];
+        let sys = TypeSystem::try_from_iter(types.into_iter().map(|ty| (ty.sem_id_unnamed(), ty)))
+            .unwrap();
+        assert_eq!(sys.optional_fields(), vec![(strct.sem_id_unnamed(), fname!("optional"))]);
+    }
+
+    #[test]
+    fn key_types() {
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let u16 = Ty::<SemId>::U16.sem_id_unnamed();
+        let types = [
+            Ty::U8,
+            Ty::U16,
+            Ty::Map(u8, u16, Sizing::U8),
+            Ty::Map(u16, u16, Sizing::U16),
+            Ty::Map(u8, u8, Sizing::U16),
+            Ty::List(u16, Sizing::U8),
+        ];
+        let sys = TypeSystem::try_from_iter(types.into_iter().map(|ty| (ty.sem_id_unnamed(), ty)))
+            .unwrap();
+        assert_eq!(sys.key_types(), bset! { u8, u16 });
+    }
+
+    #[test]
+    fn reverse_deps() {
+        let base = Ty::<SemId>::U8.sem_id_unnamed();
+        let left = Ty::<SemId>::List(base, Sizing::U8);
+        let right = Ty::<SemId>::Set(base, Sizing::U16);
+        let top = Ty::<SemId>::Struct(fields!(
+            "left" => left.sem_id_unnamed(),
+            "right" => right.sem_id_unnamed(),
+        ));
+        let types = [Ty::U8, left.clone(), right.clone(), top.clone()];
+        let sys = TypeSystem::try_from_iter(types.into_iter().map(|ty| (ty.sem_id_unnamed(), ty)))
+            .unwrap();
+        assert_eq!(sys.reverse_deps(), bmap! {
+            base => bset! { left.sem_id_unnamed(), right.sem_id_unnamed() },
+            left.sem_id_unnamed() => bset! { top.sem_id_unnamed() },
+            right.sem_id_unnamed() => bset! { top.sem_id_unnamed() }
+        });
+        for (id, referrers) in sys.reverse_deps() {
+            assert_eq!(sys.referrers(id), referrers.into_iter().collect::<Vec<_>>());
+        }
+    }
+
+    #[test]
+    fn checkpoint() {
+        let mut sys = TypeSystem::new();
+        sys.extend_checked([Ty::<SemId>::U8, Ty::U16].map(|ty| (ty.sem_id_unnamed(), ty))).unwrap();
+        let before = sys.clone();
+        let checkpoint = sys.checkpoint();
+        sys.extend_checked([Ty::<SemId>::U32, Ty::U8].map(|ty| (ty.sem_id_unnamed(), ty))).unwrap();
+        assert_eq!(sys.len(), 3);
+        sys.restore(checkpoint);
+        assert_eq!(sys, before);
+    }
+
+    #[test]
+    fn class_histogram() {
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let u16 = Ty::<SemId>::U16.sem_id_unnamed();
+        let types = [
+            Ty::U8,
+            Ty::U16,
+            Ty::Struct(fields!("first" => u8)),
+            Ty::Struct(fields!("first" => u8, "second" => u16)),
+            Ty::Map(u8, u16, Sizing::U8),
+        ];
+        let mut sys = TypeSystem::new();
+        for ty in types {
+            sys.insert_unchecked(ty.sem_id_unnamed(), ty).unwrap();
+        }
+
+        assert_eq!(sys.class_histogram(), bmap! {
+            Cls::Primitive => 2,
+            Cls::Struct => 2,
+            Cls::Map => 1
+        });
+    }
+
+    #[test]
+    fn map_ids() {
+        let u8 = Ty::<SemId>::U8.sem_id_unnamed();
+        let u16 = Ty::<SemId>::U16.sem_id_unnamed();
+        let strct = Ty::<SemId>::Struct(fields!("first" => u8, "second" => u16));
+        let list = Ty::<SemId>::List(strct.sem_id_unnamed(), Sizing::U8);
+        let sys = TypeSystem::try_from_iter(
+            [Ty::U8, Ty::U16, strct.clone(), list.clone()]
+                .into_iter()
+                .map(|ty| (ty.sem_id_unnamed(), ty)),
+        )
+        .unwrap();
+
+        let shift = |id: SemId| {
+            let mut bytes = id.to_byte_array();
+            bytes[0] = bytes[0].wrapping_add(1);
+            SemId::from(bytes)
+        };
+        let mapped = sys.clone().map_ids(shift);
+        assert_eq!(mapped.len(), sys.len());
+        for (id, ty) in sys.iter() {
+            let new = mapped.get(shift(*id)).unwrap();
+            assert_eq!(new.cls(), ty.cls());
+            for (nested, _) in new.iter() {
+                assert!(mapped.get(*nested).is_some());
+            }
+        }
+        assert_eq!(
+            mapped.get(shift(list.sem_id_unnamed())),
+            Some(&Ty::List(shift(strct.sem_id_unnamed()), Sizing::U8))
+        );
+        assert!(mapped.extract(mapped.keys().copied()).is_ok());
+    }
+}
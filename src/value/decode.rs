@@ -22,6 +22,8 @@
 
 //! Reification module: reads & writes strict values from binary strict encodings.
 
+use std::io;
+
 use amplify::ascii::AsciiString;
 use amplify::confinement::{
     Confined, LargeAscii, LargeBlob, LargeString, MediumAscii, MediumBlob, MediumString,
@@ -29,7 +31,7 @@ use amplify::confinement::{
     U32 as MAX32,
 };
 use amplify::num::{u24, u40, u48, u56};
-use encoding::{DecodeError, Primitive, ReadRaw, StreamReader, StrictDecode, StrictReader};
+use encoding::{DecodeError, Primitive, ReadRaw, Sizing, StreamReader, StrictDecode, StrictReader};
 use indexmap::IndexMap;
 
 use crate::typesys::{SymbolicSys, TypeSymbol, UnknownType};
@@ -54,8 +56,44 @@ pub enum Error {
     #[from]
     Decode(DecodeError),
 
-    /// data provided to reify operation are not entirely consumed during deserialization.
-    NotEntirelyConsumed,
+    /// data provided to reify operation are not entirely consumed during deserialization
+    /// ({remaining} bytes left).
+    NotEntirelyConsumed { remaining: usize },
+
+    /// data declare more collection elements than allowed by the decoding budget.
+    ElementBudgetExhausted,
+}
+
+/// Error decoding strict-encoded data, annotated with the byte offset at which it has happened.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display("{source} (at byte offset {offset})")]
+pub struct PositionedDecodeError {
+    /// Offset of the read operation which has failed or returned invalid data.
+    pub offset: usize,
+    pub source: Error,
+}
+
+/// Reader tracking number of consumed bytes and the offset of the last read operation.
+struct PositionedReader<R: ReadRaw> {
+    inner: R,
+    pos: usize,
+    last: usize,
+}
+
+impl<R: ReadRaw> ReadRaw for PositionedReader<R> {
+    fn read_raw<const MAX_LEN: usize>(&mut self, len: usize) -> io::Result<Vec<u8>> {
+        self.last = self.pos;
+        let data = self.inner.read_raw::<MAX_LEN>(len)?;
+        self.pos += data.len();
+        Ok(data)
+    }
+
+    fn read_raw_array<const LEN: usize>(&mut self) -> io::Result<[u8; LEN]> {
+        self.last = self.pos;
+        let data = self.inner.read_raw_array::<LEN>()?;
+        self.pos += LEN;
+        Ok(data)
+    }
 }
 
 impl SymbolicSys {
@@ -80,16 +118,33 @@ impl SymbolicSys {
     }
 }
 
+/// Maximal number of collection items for which memory is reserved upfront during decoding.
+const PREALLOC_LIMIT: usize = 0x1_0000;
+
+/// Computes how many items to reserve for a collection with a length prefix read from an
+/// untrusted source, so that a malicious length can't cause huge memory allocation.
+fn prealloc_len(len: usize, sizing: Sizing) -> usize {
+    len.min(usize::try_from(sizing.max).unwrap_or(usize::MAX)).min(PREALLOC_LIMIT)
+}
+
+fn spend_budget(budget: &mut usize, len: usize) -> Result<(), Error> {
+    *budget = budget.checked_sub(len).ok_or(Error::ElementBudgetExhausted)?;
+    Ok(())
+}
+
 impl TypeSystem {
     fn strict_read_list(
         &self,
         len: usize,
+        sizing: Sizing,
         ty: SemId,
         d: &mut impl ReadRaw,
+        budget: &mut usize,
     ) -> Result<Vec<StrictVal>, Error> {
-        let mut list = Vec::with_capacity(len);
+        spend_budget(budget, len)?;
+        let mut list = Vec::with_capacity(prealloc_len(len, sizing));
         for _ in 0..len {
-            let item = self.strict_read_type(ty, d)?;
+            let item = self.read_type(ty, d, budget)?;
             list.push(item.val);
         }
         Ok(list)
@@ -98,32 +153,95 @@ impl TypeSystem {
     fn strict_read_map(
         &self,
         len: usize,
+        sizing: Sizing,
         key_ty: SemId,
         ty: SemId,
         d: &mut impl ReadRaw,
+        budget: &mut usize,
     ) -> Result<Vec<(StrictVal, StrictVal)>, Error> {
-        let mut list = Vec::with_capacity(len);
+        spend_budget(budget, len)?;
+        let mut list = Vec::with_capacity(prealloc_len(len, sizing));
         for _ in 0..len {
-            let key = self.strict_read_type(key_ty, d)?;
-            let item = self.strict_read_type(ty, d)?;
+            let key = self.read_type(key_ty, d, budget)?;
+            let item = self.read_type(ty, d, budget)?;
             list.push((key.val, item.val));
         }
         Ok(list)
     }
 
+    /// Decodes strict-encoded `data` into a dynamic [`StrictVal`] tree, using `root` type from
+    /// the type system as a schema.
+    ///
+    /// Fails if the data do not match the schema or are not entirely consumed.
+    pub fn decode_value(&self, root: SemId, data: &[u8]) -> Result<StrictVal, Error> {
+        self.strict_deserialize_type(root, data).map(|typed| typed.val)
+    }
+
     pub fn strict_deserialize_type(&self, sem_id: SemId, data: &[u8]) -> Result<TypedVal, Error> {
+        self.strict_deserialize_type_bounded(sem_id, data, usize::MAX)
+    }
+
+    /// Deserializes strict-encoded data like [`Self::strict_deserialize_type`], reporting the
+    /// byte offset at which the decoding has failed.
+    pub fn strict_deserialize_positioned(
+        &self,
+        sem_id: SemId,
+        data: &[u8],
+    ) -> Result<TypedVal, PositionedDecodeError> {
+        let mut reader = PositionedReader {
+            inner: StreamReader::cursor::<MAX32>(data),
+            pos: 0,
+            last: 0,
+        };
+        let ty =
+            self.strict_read_type(sem_id, &mut reader).map_err(|source| PositionedDecodeError {
+                offset: reader.last,
+                source,
+            })?;
+        if reader.pos != data.len() {
+            return Err(PositionedDecodeError {
+                offset: reader.pos,
+                source: Error::NotEntirelyConsumed {
+                    remaining: data.len() - reader.pos,
+                },
+            });
+        }
+        Ok(ty)
+    }
+
+    /// Deserializes strict-encoded data like [`Self::strict_deserialize_type`], limiting the
+    /// total number of elements in all collections (including nested ones) by `max_elements`.
+    ///
+    /// This protects from small inputs declaring huge numbers of elements in nested
+    /// collections, with each of the collections being within its own size bounds.
+    pub fn strict_deserialize_type_bounded(
+        &self,
+        sem_id: SemId,
+        data: &[u8],
+        max_elements: usize,
+    ) -> Result<TypedVal, Error> {
         let mut cursor = StreamReader::cursor::<MAX32>(data);
-        let ty = self.strict_read_type(sem_id, &mut cursor)?;
-        if cursor.unconfine().position() as usize != data.len() {
-            return Err(Error::NotEntirelyConsumed);
+        let mut budget = max_elements;
+        let ty = self.read_type(sem_id, &mut cursor, &mut budget)?;
+        let pos = cursor.unconfine().position() as usize;
+        if pos != data.len() {
+            return Err(Error::NotEntirelyConsumed {
+                remaining: data.len() - pos,
+            });
         }
         Ok(ty)
     }
 
-    pub fn strict_read_type(
+    pub fn strict_read_type(&self, sem_id: SemId, d: &mut impl ReadRaw) -> Result<TypedVal, Error> {
+        let mut budget = usize::MAX;
+        self.read_type(sem_id, d, &mut budget)
+    }
+
+    fn read_type(
         &self,
         sem_id: SemId,
         mut d: &mut impl ReadRaw,
+        budget: &mut usize,
     ) -> Result<TypedVal, Error> {
         let spec = TypeSpec::from(sem_id);
         let ty = self.find(sem_id).ok_or_else(|| Error::TypeAbsent(spec.clone()))?;
@@ -218,14 +336,14 @@ impl TypeSystem {
                 let Some((variant, ty)) = variants.by_tag(tag) else {
                     return Err(DecodeError::EnumTagNotKnown(spec.to_string(), tag).into());
                 };
-                let fields = self.strict_read_type(*ty, reader.unbox())?;
+                let fields = self.read_type(*ty, reader.unbox(), budget)?;
                 StrictVal::union(variant.name.clone(), fields.val)
             }
             Ty::Tuple(reqs) => {
                 let mut fields = Vec::with_capacity(reqs.len());
                 let d = reader.unbox();
                 for ty in reqs {
-                    let checked = self.strict_read_type(*ty, d)?;
+                    let checked = self.read_type(*ty, d, budget)?;
                     fields.push(checked.val);
                 }
                 StrictVal::tuple(fields)
@@ -234,7 +352,7 @@ impl TypeSystem {
                 let mut fields = IndexMap::with_capacity(reqs.len());
                 let d = reader.unbox();
                 for field in reqs {
-                    let checked = self.strict_read_type(field.ty, d)?;
+                    let checked = self.read_type(field.ty, d, budget)?;
                     fields.insert(field.name.clone(), checked.val);
                 }
                 StrictVal::Struct(fields)
@@ -247,10 +365,11 @@ impl TypeSystem {
                 StrictVal::Bytes(Blob(buf))
             }
             Ty::Array(ty, len) => {
+                spend_budget(budget, *len as usize)?;
                 let mut list = Vec::<StrictVal>::with_capacity(*len as usize);
                 let d = reader.unbox();
                 for _ in 0..*len {
-                    let checked = self.strict_read_type(*ty, d)?;
+                    let checked = self.read_type(*ty, d, budget)?;
                     list.push(checked.val);
                 }
                 StrictVal::List(list)
@@ -296,92 +415,93 @@ impl TypeSystem {
             Ty::List(ty, sizing) if sizing.max <= u8::MAX as u64 => {
                 let len = u8::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_list(len as usize, *ty, d)?;
+                let list = self.strict_read_list(len as usize, *sizing, *ty, d, budget)?;
                 StrictVal::List(list)
             }
             Ty::List(ty, sizing) if sizing.max <= u16::MAX as u64 => {
                 let len = u16::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_list(len as usize, *ty, d)?;
+                let list = self.strict_read_list(len as usize, *sizing, *ty, d, budget)?;
                 StrictVal::List(list)
             }
             Ty::List(ty, sizing) if sizing.max <= u24::MAX.into_u64() => {
                 let len = u24::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_list(len.into_usize(), *ty, d)?;
+                let list = self.strict_read_list(len.into_usize(), *sizing, *ty, d, budget)?;
                 StrictVal::List(list)
             }
             Ty::List(ty, sizing) if sizing.max <= u32::MAX as u64 => {
                 let len = u32::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_list(len as usize, *ty, d)?;
+                let list = self.strict_read_list(len as usize, *sizing, *ty, d, budget)?;
                 StrictVal::List(list)
             }
-            Ty::List(ty, _) => {
+            Ty::List(ty, sizing) => {
                 let len = u64::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_list(len as usize, *ty, d)?;
+                let list = self.strict_read_list(len as usize, *sizing, *ty, d, budget)?;
                 StrictVal::List(list)
             }
             // TODO: Find a way to check for the uniqueness of the set values
             Ty::Set(ty, sizing) if sizing.max <= u8::MAX as u64 => {
                 let len = u8::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_list(len as usize, *ty, d)?;
+                let list = self.strict_read_list(len as usize, *sizing, *ty, d, budget)?;
                 StrictVal::Set(list)
             }
             Ty::Set(ty, sizing) if sizing.max <= u16::MAX as u64 => {
                 let len = u16::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_list(len as usize, *ty, d)?;
+                let list = self.strict_read_list(len as usize, *sizing, *ty, d, budget)?;
                 StrictVal::Set(list)
             }
             Ty::Set(ty, sizing) if sizing.max <= u24::MAX.into_u64() => {
                 let len = u24::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_list(len.into_usize(), *ty, d)?;
+                let list = self.strict_read_list(len.into_usize(), *sizing, *ty, d, budget)?;
                 StrictVal::Set(list)
             }
             Ty::Set(ty, sizing) if sizing.max <= u32::MAX as u64 => {
                 let len = u32::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_list(len as usize, *ty, d)?;
+                let list = self.strict_read_list(len as usize, *sizing, *ty, d, budget)?;
                 StrictVal::Set(list)
             }
-            Ty::Set(ty, _) => {
+            Ty::Set(ty, sizing) => {
                 let len = u64::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_list(len as usize, *ty, d)?;
+                let list = self.strict_read_list(len as usize, *sizing, *ty, d, budget)?;
                 StrictVal::Set(list)
             }
             Ty::Map(key_id, id, sizing) if sizing.max <= u8::MAX as u64 => {
                 let len = u8::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_map(len as usize, *key_id, *id, d)?;
+                let list = self.strict_read_map(len as usize, *sizing, *key_id, *id, d, budget)?;
                 StrictVal::Map(list)
             }
             Ty::Map(key_id, id, sizing) if sizing.max <= u16::MAX as u64 => {
                 let len = u16::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_map(len as usize, *key_id, *id, d)?;
+                let list = self.strict_read_map(len as usize, *sizing, *key_id, *id, d, budget)?;
                 StrictVal::Map(list)
             }
             Ty::Map(key_id, id, sizing) if sizing.max <= u24::MAX.into_u64() => {
                 let len = u24::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_map(len.into_usize(), *key_id, *id, d)?;
+                let list =
+                    self.strict_read_map(len.into_usize(), *sizing, *key_id, *id, d, budget)?;
                 StrictVal::Map(list)
             }
             Ty::Map(key_id, id, sizing) if sizing.max <= u32::MAX as u64 => {
                 let len = u32::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_map(len as usize, *key_id, *id, d)?;
+                let list = self.strict_read_map(len as usize, *sizing, *key_id, *id, d, budget)?;
                 StrictVal::Map(list)
             }
-            Ty::Map(key_id, id, _sizing) => {
+            Ty::Map(key_id, id, sizing) => {
                 let len = u64::strict_decode(&mut reader)?;
                 d = reader.unbox();
-                let list = self.strict_read_map(len as usize, *key_id, *id, d)?;
+                let list = self.strict_read_map(len as usize, *sizing, *key_id, *id, d, budget)?;
                 StrictVal::Map(list)
             }
         };
@@ -395,8 +515,10 @@ impl TypeSystem {
 
 #[cfg(test)]
 mod test {
+    use amplify::confinement::Confined;
+
     use super::super::test_helpers::*;
-    // use super::*;
+    use super::*;
 
     #[test]
     fn typify() {
@@ -409,4 +531,98 @@ mod test {
             r#"name "Some name", ticker "TICK", precision twoDecimals"#
         );
     }
+
+    #[test]
+    fn huge_len_prefix() {
+        let item = Ty::<SemId>::U16;
+        let list = Ty::<SemId>::List(item.sem_id_unnamed(), Sizing::new(0, u32::MAX as u64));
+        let list_id = list.sem_id_unnamed();
+        let sys = TypeSystem::from(Confined::from_checked(bmap! {
+            item.sem_id_unnamed() => item,
+            list_id => list
+        }));
+
+        assert_eq!(
+            prealloc_len(u32::MAX as usize, Sizing::new(0, u32::MAX as u64)),
+            PREALLOC_LIMIT
+        );
+        assert_eq!(prealloc_len(10, Sizing::U8), 10);
+        let err = sys.strict_deserialize_type(list_id, &[0xFF, 0xFF, 0xFF, 0xFF]).unwrap_err();
+        assert!(matches!(err, Error::Decode(_)));
+    }
+
+    #[test]
+    fn positioned_error() {
+        let u16 = Ty::<SemId>::U16;
+        let enm = Ty::<SemId>::Enum(variants!("a", "b"));
+        let rec = Ty::<SemId>::Struct(fields!(
+            "id" => u16.sem_id_unnamed(),
+            "kind" => enm.sem_id_unnamed(),
+        ));
+        let rec_id = rec.sem_id_unnamed();
+        let types = [u16, enm, rec].into_iter().map(|ty| (ty.sem_id_unnamed(), ty));
+        let sys = TypeSystem::from(Confined::from_iter_checked(types));
+
+        sys.strict_deserialize_positioned(rec_id, &[1, 0, 1]).unwrap();
+        let err = sys.strict_deserialize_positioned(rec_id, &[1, 0, 7]).unwrap_err();
+        assert_eq!(err.offset, 2);
+        assert!(matches!(err.source, Error::Decode(DecodeError::EnumTagNotKnown(_, 7))));
+        let err = sys.strict_deserialize_positioned(rec_id, &[1, 0, 1, 0]).unwrap_err();
+        assert_eq!(err.offset, 3);
+        assert_eq!(err.source, Error::NotEntirelyConsumed { remaining: 1 });
+    }
+
+    #[test]
+    fn decode_value_option() {
+        let unit = Ty::<SemId>::UNIT;
+        let u8 = Ty::<SemId>::U8;
+        let opt = Ty::<SemId>::Union(variants!(
+            "none" => unit.sem_id_unnamed(),
+            "some" => u8.sem_id_unnamed(),
+        ));
+        let rec = Ty::<SemId>::Struct(fields!(
+            "id" => u8.sem_id_unnamed(),
+            "extra" => opt.sem_id_unnamed(),
+        ));
+        let rec_id = rec.sem_id_unnamed();
+        let types = [unit, u8, opt, rec].into_iter().map(|ty| (ty.sem_id_unnamed(), ty));
+        let sys = TypeSystem::from(Confined::from_iter_checked(types));
+
+        let present = sys.decode_value(rec_id, &[7, 1, 9]).unwrap();
+        assert_eq!(
+            present,
+            StrictVal::struc([("id", svnum!(7u8)), ("extra", StrictVal::some(svnum!(9u8)))])
+        );
+        let absent = sys.decode_value(rec_id, &[7, 0]).unwrap();
+        assert_eq!(absent, StrictVal::struc([("id", svnum!(7u8)), ("extra", StrictVal::none())]));
+        assert_eq!(
+            sys.decode_value(rec_id, &[7, 0, 0, 0, 0]),
+            Err(Error::NotEntirelyConsumed { remaining: 3 })
+        );
+    }
+
+    #[test]
+    fn element_budget() {
+        let item = Ty::<SemId>::U16;
+        let inner = Ty::<SemId>::List(item.sem_id_unnamed(), Sizing::U8);
+        let outer = Ty::<SemId>::List(inner.sem_id_unnamed(), Sizing::U8);
+        let outer_id = outer.sem_id_unnamed();
+        let types = [item, inner, outer].into_iter().map(|ty| (ty.sem_id_unnamed(), ty));
+        let sys = TypeSystem::from(Confined::from_iter_checked(types));
+
+        let mut data = vec![3u8];
+        for _ in 0..3 {
+            data.extend([3, 1, 0, 2, 0, 3, 0]);
+        }
+        sys.strict_deserialize_type(outer_id, &data).unwrap();
+        sys.strict_deserialize_type_bounded(outer_id, &data, 12).unwrap();
+        assert_eq!(
+            sys.strict_deserialize_type_bounded(outer_id, &data, 11).unwrap_err(),
+            Error::ElementBudgetExhausted
+        );
+        assert_eq!(
+            sys.strict_deserialize_type_bounded(outer_id, &[0xFF], 100).unwrap_err(),
+            Error::ElementBudgetExhausted
+        );
+    }
 }
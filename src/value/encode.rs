@@ -23,14 +23,14 @@
 use std::io;
 
 use amplify::confinement::{Confined, ConfinedBlob};
-use amplify::num::u24;
 use encoding::{
-    Primitive, SerializeError, Sizing, StrictEncode, StrictSerialize, StrictType, TypeName,
-    TypedWrite, WriteRaw,
+    Primitive, SerializeError, StrictEncode, StrictSerialize, StrictType, TypeName, TypedWrite,
+    WriteRaw,
 };
 
-use crate::typify::TypedVal;
-use crate::value::{EnumTag, StrictNum};
+use crate::ast::SizingExt;
+use crate::typify::{self, TypedVal};
+use crate::value::{EnumTag, KeyStep, Path, Step, StrictNum};
 use crate::{SemId, StrictVal, Ty, TypeSystem};
 
 #[derive(Clone, Debug)]
@@ -50,7 +50,102 @@ impl<const MAX_LEN: usize> StrictEncode for SerializedType<MAX_LEN> {
 }
 impl<const MAX_LEN: usize> StrictSerialize for SerializedType<MAX_LEN> {}
 
+/// Error encoding strict value: the value doesn't match the type at the given path.
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display("value at path `{path}` doesn't match its type: {error}")]
+pub struct EncodeError {
+    pub path: Path,
+    pub error: typify::Error,
+}
+
 impl TypeSystem {
+    /// Encodes dynamic strict value according to the `root` type from the type system.
+    ///
+    /// The value is checked to match the type; on mismatch the returned error contains path to
+    /// the first offending value.
+    pub fn encode_value(&self, root: SemId, val: &StrictVal) -> Result<Vec<u8>, EncodeError> {
+        let typed = self.typify(val.clone(), root).map_err(|error| {
+            let mut path = Path::new();
+            let error = self.locate_mismatch(val, root, &mut path).unwrap_or(error);
+            EncodeError { path, error }
+        })?;
+        let mut buf = Vec::new();
+        self.strict_write_value(&typed, &mut buf).expect("in-memory writer");
+        Ok(buf)
+    }
+
+    /// Descends into the first value component which fails typification, returning its error
+    /// and extending `path` with the steps leading to it.
+    fn locate_mismatch(
+        &self,
+        val: &StrictVal,
+        sem_id: SemId,
+        path: &mut Path,
+    ) -> Option<typify::Error> {
+        let ty = self.find(sem_id)?;
+        let step = |step: Step| Some(step);
+        let children: Vec<(Option<Step>, &StrictVal, SemId)> = match (val, ty) {
+            (StrictVal::Struct(fields), Ty::Struct(reqs)) => fields
+                .iter()
+                .filter_map(|(name, val)| {
+                    reqs.ty_by_name(name).map(|id| (step(Step::NamedField(name.clone())), val, *id))
+                })
+                .collect(),
+            (StrictVal::Tuple(fields) | StrictVal::List(fields), Ty::Tuple(reqs))
+                if fields.len() == reqs.len() =>
+            {
+                fields
+                    .iter()
+                    .zip(reqs)
+                    .enumerate()
+                    .map(|(no, (val, id))| (step(Step::UnnamedField(no as u8)), val, *id))
+                    .collect()
+            }
+            (StrictVal::List(items), Ty::Array(id, _) | Ty::List(id, _))
+            | (StrictVal::Set(items), Ty::Set(id, _)) => items
+                .iter()
+                .enumerate()
+                .map(|(no, val)| (step(Step::Index(no as u32)), val, *id))
+                .collect(),
+            (StrictVal::Map(items), Ty::Map(key_id, id, _)) => items
+                .iter()
+                .enumerate()
+                .flat_map(|(no, (key, val))| {
+                    let key_step = match key {
+                        StrictVal::Number(StrictNum::Uint(num)) => {
+                            Step::Key(KeyStep::Number(*num as u128))
+                        }
+                        StrictVal::String(s) => Confined::try_from(s.clone())
+                            .map(|s| Step::Key(KeyStep::TinyString(s)))
+                            .unwrap_or(Step::Index(no as u32)),
+                        _ => Step::Index(no as u32),
+                    };
+                    [(step(key_step.clone()), key, *key_id), (step(key_step), val, *id)]
+                })
+                .collect(),
+            (StrictVal::Union(tag, val), Ty::Union(variants)) => match tag {
+                EnumTag::Name(name) => variants.ty_by_name(name),
+                EnumTag::Ord(ord) => variants.ty_by_tag(*ord),
+            }
+            .map(|id| vec![(None, val.as_ref(), *id)])
+            .unwrap_or_default(),
+            // Implicit `some` of an optional value
+            (val, ty) if ty.is_option() => {
+                vec![(None, val, *ty.as_some().expect("checked by is_option"))]
+            }
+            _ => vec![],
+        };
+        for (step, val, id) in children {
+            if let Err(err) = self.typify(val.clone(), id) {
+                if let Some(step) = step {
+                    path.push(step).expect("confinement collection guarantees");
+                }
+                return Some(self.locate_mismatch(val, id, path).unwrap_or(err));
+            }
+        }
+        None
+    }
+
     pub fn strict_serialize_value<const MAX_LEN: usize>(
         &self,
         typed: &TypedVal,
@@ -224,18 +319,44 @@ impl TypeSystem {
     }
 }
 
-trait SizingExt {
-    fn byte_size(&self) -> usize;
-}
+#[cfg(test)]
+mod test {
+    use encoding::Sizing;
 
-impl SizingExt for Sizing {
-    fn byte_size(&self) -> usize {
-        match self.max {
-            one if one <= u8::MAX as u64 => 1,
-            two if two <= u16::MAX as u64 => 2,
-            three if three <= u24::MAX.into_u64() => 3,
-            four if four <= u32::MAX as u64 => 4,
-            _ => 8,
-        }
+    use super::*;
+
+    fn system() -> (TypeSystem, SemId) {
+        let u8 = Ty::<SemId>::U8;
+        let u32 = Ty::<SemId>::U32;
+        let list = Ty::<SemId>::List(u32.sem_id_unnamed(), Sizing::new(0, u8::MAX as u64));
+        let inner = Ty::<SemId>::Struct(fields!(
+            "flag" => u8.sem_id_unnamed(),
+            "items" => list.sem_id_unnamed(),
+        ));
+        let outer = Ty::<SemId>::Struct(fields!(
+            "id" => u32.sem_id_unnamed(),
+            "inner" => inner.sem_id_unnamed(),
+        ));
+        let outer_id = outer.sem_id_unnamed();
+        let types = [u8, u32, list, inner, outer].into_iter().map(|ty| (ty.sem_id_unnamed(), ty));
+        (TypeSystem::from(Confined::from_iter_checked(types)), outer_id)
+    }
+
+    #[test]
+    fn encode_round_trip() {
+        let (sys, root) = system();
+        let val = ston!(id 5u32, inner ston!(flag 1u8, items svlist!([1u32, 2u32])));
+        let data = sys.encode_value(root, &val).unwrap();
+        assert_eq!(data, [5, 0, 0, 0, 1, 2, 1, 0, 0, 0, 2, 0, 0, 0]);
+        assert_eq!(sys.decode_value(root, &data).unwrap(), val);
+    }
+
+    #[test]
+    fn encode_mismatch() {
+        let (sys, root) = system();
+        let val = ston!(id 5u32, inner ston!(flag 1u8, items svlist!([svnum!(1u32), svstr!("x")])));
+        let err = sys.encode_value(root, &val).unwrap_err();
+        assert_eq!(err.path.to_string(), ".inner.items[1]");
+        assert!(matches!(err.error, typify::Error::TypeMismatch { .. }));
     }
 }
@@ -24,6 +24,7 @@
 //! - [`path`]: path accessors/introspects into strict values;
 //! - [STON][ston]: strict type object notation, a JSON-like representation of strict types;
 //! - [`decode`]: conversion between strict encoding and strict values;
+//! - [`validate`]: validation of strict encoding against strict type schema;
 //! - [`typify`]: checks of strict values against strict type schema;
 //! - [`convert`]: conversion between strict values and other text representations (JSON, YAML,
 //!   TOML, etc).
@@ -34,10 +35,12 @@ mod path;
 pub mod ston;
 pub mod typify;
 pub mod decode;
+pub mod validate;
 #[cfg(feature = "serde")]
 pub mod convert;
 mod encode;
 
+pub use encode::EncodeError;
 pub use path::{KeyStep, Path, PathError, Step};
 pub use val::{Blob, EnumTag, StrictNum, StrictVal};
 
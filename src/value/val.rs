@@ -560,6 +560,74 @@ impl StrictVal {
     }
 }
 
+#[cfg(feature = "serde")]
+impl StrictNum {
+    /// Converts the number into JSON; big integers not fitting JSON numbers are represented as
+    /// decimal strings.
+    pub fn to_json(&self) -> serde_json::Value {
+        match self {
+            StrictNum::Uint(v) => serde_json::Value::from(*v),
+            StrictNum::Int(v) => serde_json::Value::from(*v),
+            StrictNum::BigUint(v) => serde_json::Value::String(v.to_string()),
+            StrictNum::BigInt(v) => serde_json::Value::String(v.to_string()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl StrictVal {
+    /// Converts the value into JSON for inspection and debugging purposes.
+    ///
+    /// Byte strings are represented as hex strings, option `none` as `null` and `some` as its
+    /// inner value. Maps with string keys are converted into JSON objects, while maps with other
+    /// key types become arrays of key-value pairs.
+    pub fn to_json(&self) -> serde_json::Value {
+        use serde_json::{Map, Value};
+
+        match self {
+            StrictVal::Unit => Value::Null,
+            StrictVal::Number(num) => num.to_json(),
+            StrictVal::String(s) => Value::String(s.clone()),
+            StrictVal::Bytes(blob) => Value::String(blob.to_hex()),
+            StrictVal::Struct(fields) => Value::Object(
+                fields.iter().map(|(name, val)| (name.to_string(), val.to_json())).collect(),
+            ),
+            StrictVal::Enum(EnumTag::Name(name)) => Value::String(name.to_string()),
+            StrictVal::Enum(EnumTag::Ord(tag)) => Value::from(*tag),
+            StrictVal::Union(tag, _) if tag == &EnumTag::from("none") => Value::Null,
+            StrictVal::Union(tag, val) if tag == &EnumTag::from("some") => val.to_json(),
+            StrictVal::Union(tag, val) if **val == StrictVal::Unit => {
+                Value::String(tag.to_string())
+            }
+            StrictVal::Union(tag, val) => {
+                Value::Object(Map::from_iter([(tag.to_string(), val.to_json())]))
+            }
+            StrictVal::List(items) | StrictVal::Set(items) | StrictVal::Tuple(items) => {
+                Value::Array(items.iter().map(StrictVal::to_json).collect())
+            }
+            StrictVal::Map(map)
+                if map.iter().all(|(key, _)| matches!(key, StrictVal::String(_))) =>
+            {
+                Value::Object(
+                    map.iter()
+                        .map(|(key, val)| {
+                            let StrictVal::String(key) = key else {
+                                unreachable!()
+                            };
+                            (key.clone(), val.to_json())
+                        })
+                        .collect(),
+                )
+            }
+            StrictVal::Map(map) => Value::Array(
+                map.iter()
+                    .map(|(key, val)| Value::Array(vec![key.to_json(), val.to_json()]))
+                    .collect(),
+            ),
+        }
+    }
+}
+
 impl<T: Into<StrictVal>> From<Option<T>> for StrictVal {
     fn from(value: Option<T>) -> Self {
         match value {
@@ -588,4 +656,21 @@ mod test {
             r#"Struct({FieldName("name"): String("Some name"), FieldName("ticker"): String("TICK"), FieldName("precision"): Number(Uint(8))})"#
         )
     }
+
+    #[test]
+    #[cfg(feature = "serde")]
+    fn to_json() {
+        use serde_json::json;
+
+        use super::*;
+
+        let strct = ston!(name "Some name", data svbytes!([0xca, 0xfe]), count svnone!());
+        assert_eq!(strct.to_json(), json!({"name": "Some name", "data": "cafe", "count": null}));
+        assert_eq!(svsome!(5u8).to_json(), json!(5));
+        assert_eq!(svlist!([1u8, 2u8, 3u8]).to_json(), json!([1, 2, 3]));
+        let map = StrictVal::Map(vec![(svnum!(1u8), svstr!("one"))]);
+        assert_eq!(map.to_json(), json!([[1, "one"]]));
+        let map = StrictVal::Map(vec![(svstr!("one"), svnum!(1u8))]);
+        assert_eq!(map.to_json(), json!({"one": 1}));
+    }
 }
@@ -0,0 +1,310 @@
+// Strict encoding schema library, implementing validation and parsing
+// strict encoded data against a schema.
+//
+// SPDX-License-Identifier: Apache-2.0
+//
+// Written in 2022-2024 by
+//     Dr. Maxim Orlovsky <orlovsky@ubideco.org>
+//
+// Copyright 2022-2024 UBIDECO Institute
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Validation of strict-encoded data against a type from a type system, without constructing
+//! strict values.
+
+use encoding::{Primitive, Sizing};
+
+use crate::ast::{SizingExt, UnnamedFields};
+use crate::{Cls, SemId, Ty, TypeSystem};
+
+#[derive(Clone, Eq, PartialEq, Debug, Display, Error)]
+#[display(doc_comments)]
+pub enum ValidationError {
+    /// type `{0}` is not a part of the type system.
+    TypeAbsent(SemId),
+
+    /// unexpected end of data at byte offset {offset} while reading {expected} type `{ty}`.
+    UnexpectedEnd {
+        offset: usize,
+        expected: Cls,
+        ty: SemId,
+    },
+
+    /// unknown tag {tag} at byte offset {offset} for {expected} type `{ty}`.
+    InvalidTag {
+        offset: usize,
+        expected: Cls,
+        ty: SemId,
+        tag: u8,
+    },
+
+    /// length {len} at byte offset {offset} is outside of {min}..={max} range allowed for
+    /// {expected} type `{ty}`.
+    InvalidLen {
+        offset: usize,
+        expected: Cls,
+        ty: SemId,
+        len: u64,
+        min: u64,
+        max: u64,
+    },
+
+    /// invalid UTF-8 string at byte offset {offset} for type `{ty}`.
+    InvalidUtf8 { offset: usize, ty: SemId },
+
+    /// data contain {remaining} trailing bytes starting from byte offset {offset}.
+    TrailingBytes { offset: usize, remaining: usize },
+}
+
+impl TypeSystem {
+    /// Validates that `data` contain exactly one strict-encoded value of the `root` type.
+    ///
+    /// The type is walked structurally, consuming data bytes according to the type schema,
+    /// without constructing the value. On failure the error reports byte offset at which the
+    /// data diverge from the schema and the class of the type which was expected there.
+    pub fn validate(&self, root: SemId, data: &[u8]) -> Result<(), ValidationError> {
+        let mut validator = Validator {
+            sys: self,
+            data,
+            pos: 0,
+        };
+        validator.validate(root)?;
+        if validator.pos != data.len() {
+            return Err(ValidationError::TrailingBytes {
+                offset: validator.pos,
+                remaining: data.len() - validator.pos,
+            });
+        }
+        Ok(())
+    }
+}
+
+struct Validator<'a> {
+    sys: &'a TypeSystem,
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Validator<'a> {
+    fn ty(&self, id: SemId) -> Result<&'a Ty<SemId>, ValidationError> {
+        self.sys.get(id).ok_or(ValidationError::TypeAbsent(id))
+    }
+
+    fn take(&mut self, len: usize, ty: SemId, expected: Cls) -> Result<&'a [u8], ValidationError> {
+        let end = self.pos.checked_add(len).filter(|end| *end <= self.data.len()).ok_or(
+            ValidationError::UnexpectedEnd {
+                offset: self.pos,
+                expected,
+                ty,
+            },
+        )?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn tag(&mut self, ty: SemId, expected: Cls, known: bool) -> Result<(), ValidationError> {
+        if !known {
+            let offset = self.pos - 1;
+            return Err(ValidationError::InvalidTag {
+                offset,
+                expected,
+                ty,
+                tag: self.data[offset],
+            });
+        }
+        Ok(())
+    }
+
+    fn len(&mut self, sizing: Sizing, ty: SemId, expected: Cls) -> Result<usize, ValidationError> {
+        let offset = self.pos;
+        let mut buf = [0u8; 8];
+        let bytes = self.take(sizing.byte_size(), ty, expected)?;
+        buf[..bytes.len()].copy_from_slice(bytes);
+        let len = u64::from_le_bytes(buf);
+        if len < sizing.min || len > sizing.max {
+            return Err(ValidationError::InvalidLen {
+                offset,
+                expected,
+                ty,
+                len,
+                min: sizing.min,
+                max: sizing.max,
+            });
+        }
+        Ok(len as usize)
+    }
+
+    fn validate(&mut self, id: SemId) -> Result<(), ValidationError> {
+        let ty = self.ty(id)?;
+        let cls = ty.cls();
+        match ty {
+            Ty::Primitive(prim) if *prim == Primitive::UNIT => {}
+            Ty::Primitive(prim) if *prim == Primitive::BYTE => {
+                self.take(1, id, cls)?;
+            }
+            Ty::Primitive(prim) => {
+                self.take(prim.byte_size() as usize, id, cls)?;
+            }
+            Ty::UnicodeChar => {
+                let offset = self.pos;
+                let first = self.take(1, id, cls)?[0];
+                let width = match first {
+                    0x00..=0x7F => 1,
+                    0xC0..=0xDF => 2,
+                    0xE0..=0xEF => 3,
+                    0xF0..=0xF7 => 4,
+                    _ => return Err(ValidationError::InvalidUtf8 { offset, ty: id }),
+                };
+                self.take(width - 1, id, cls)?;
+                if std::str::from_utf8(&self.data[offset..self.pos]).is_err() {
+                    return Err(ValidationError::InvalidUtf8 { offset, ty: id });
+                }
+            }
+            Ty::Enum(variants) => {
+                let tag = self.take(1, id, cls)?[0];
+                self.tag(id, cls, variants.iter().any(|v| v.tag == tag))?;
+            }
+            Ty::Union(variants) => {
+                let tag = self.take(1, id, cls)?[0];
+                let inner = variants.ty_by_tag(tag);
+                self.tag(id, cls, inner.is_some())?;
+                self.validate(*inner.expect("checked above"))?;
+            }
+            Ty::Tuple(fields) if self.sys.is_rstring(fields).unwrap_or_default() => {
+                self.validate_rstring(id, fields)?;
+            }
+            Ty::Tuple(fields) => {
+                for field in fields {
+                    self.validate(*field)?;
+                }
+            }
+            Ty::Struct(fields) => {
+                for field in fields {
+                    self.validate(field.ty)?;
+                }
+            }
+            Ty::Array(inner, len) => {
+                for _ in 0..*len {
+                    self.validate(*inner)?;
+                }
+            }
+            Ty::List(inner, sizing) if self.ty(*inner)?.is_unicode_char() => {
+                let len = self.len(*sizing, id, cls)?;
+                let offset = self.pos;
+                let bytes = self.take(len, id, cls)?;
+                if std::str::from_utf8(bytes).is_err() {
+                    return Err(ValidationError::InvalidUtf8 { offset, ty: id });
+                }
+            }
+            Ty::List(inner, sizing) | Ty::Set(inner, sizing) => {
+                for _ in 0..self.len(*sizing, id, cls)? {
+                    self.validate(*inner)?;
+                }
+            }
+            Ty::Map(key, value, sizing) => {
+                for _ in 0..self.len(*sizing, id, cls)? {
+                    self.validate(*key)?;
+                    self.validate(*value)?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Restricted strings are encoded as a single ASCII string, with the first character coming
+    /// from a different charset than the rest of the characters.
+    fn validate_rstring(
+        &mut self,
+        id: SemId,
+        fields: &UnnamedFields<SemId>,
+    ) -> Result<(), ValidationError> {
+        let cls = Cls::AsciiStr;
+        let (rest, sizing) = self
+            .sys
+            .rstring_sizing(fields)
+            .map_err(|_| ValidationError::TypeAbsent(fields[1]))?
+            .expect("checked by is_rstring");
+        let len = self.len(sizing, id, cls)?;
+        for pos in 0..len {
+            let char_id = if pos == 0 { fields[0] } else { rest };
+            let Ty::Enum(variants) = self.ty(char_id)? else {
+                unreachable!("rstring characters are always enums")
+            };
+            let tag = self.take(1, id, cls)?[0];
+            self.tag(char_id, cls, variants.iter().any(|v| v.tag == tag))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use amplify::confinement::Confined;
+
+    use super::*;
+
+    fn system() -> (TypeSystem, SemId) {
+        let u16 = Ty::<SemId>::U16;
+        let u32 = Ty::<SemId>::U32;
+        let list = Ty::<SemId>::List(u32.sem_id_unnamed(), Sizing::new(1, u8::MAX as u64));
+        let rec = Ty::<SemId>::Struct(fields!(
+            "id" => u16.sem_id_unnamed(),
+            "items" => list.sem_id_unnamed(),
+        ));
+        let rec_id = rec.sem_id_unnamed();
+        let types = [u16, u32, list, rec].into_iter().map(|ty| (ty.sem_id_unnamed(), ty));
+        (TypeSystem::from(Confined::from_iter_checked(types)), rec_id)
+    }
+
+    #[test]
+    fn valid() {
+        let (sys, rec) = system();
+        sys.validate(rec, &[1, 0, 2, 1, 0, 0, 0, 2, 0, 0, 0]).unwrap();
+    }
+
+    #[test]
+    fn truncated() {
+        let (sys, rec) = system();
+        let u32 = Ty::<SemId>::U32.sem_id_unnamed();
+        assert_eq!(
+            sys.validate(rec, &[1, 0, 2, 1, 0, 0, 0, 2, 0, 0]),
+            Err(ValidationError::UnexpectedEnd {
+                offset: 7,
+                expected: Cls::Primitive,
+                ty: u32,
+            })
+        );
+    }
+
+    #[test]
+    fn invalid_len_and_trailing() {
+        let (sys, rec) = system();
+        assert!(matches!(
+            sys.validate(rec, &[1, 0, 0]),
+            Err(ValidationError::InvalidLen {
+                offset: 2,
+                len: 0,
+                ..
+            })
+        ));
+        assert_eq!(
+            sys.validate(rec, &[1, 0, 1, 1, 0, 0, 0, 0xFF]),
+            Err(ValidationError::TrailingBytes {
+                offset: 7,
+                remaining: 1
+            })
+        );
+    }
+}